@@ -0,0 +1,58 @@
+//! Counts the allocations made while reading AnimX, on the animations from [``resonite_core::animation::fixtures``]
+//!
+//! Criterion only measures time, so this just prints the counts\
+//! ``cargo bench --features testing --bench allocations``
+
+use std::{alloc::{GlobalAlloc, Layout, System}, hint::black_box, sync::atomic::{AtomicUsize, Ordering}};
+
+use resonite_core::animation::{Animation, fixtures};
+
+/// Passes everything on to the system allocator, counting the allocations and bytes on the way
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(new_size, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Allocations and allocated bytes (reallocations included) while running ``f``
+fn count<T>(f: impl FnOnce() -> T) -> (usize, usize) {
+    let (allocations, bytes) = (ALLOCATIONS.load(Ordering::Relaxed), BYTES.load(Ordering::Relaxed));
+    black_box(f());
+    (ALLOCATIONS.load(Ordering::Relaxed) - allocations, BYTES.load(Ordering::Relaxed) - bytes)
+}
+
+fn main() {
+    let animations = [
+        ("long_curve", fixtures::long_curve(100_000)),
+        ("many_tracks", fixtures::many_tracks(2000, 10)),
+        ("matrix_track", fixtures::matrix_track(10_000)),
+    ];
+
+    for (name, anim) in animations {
+        let buf = anim.to_animx_bytes().unwrap();
+        let (allocations, bytes) = count(|| Animation::from_animx(&buf[..]).unwrap());
+        println!("from_animx/{name}: {allocations} allocations, {bytes} bytes");
+        let (allocations, bytes) = count(|| Animation::from_animx_slice(&buf).unwrap());
+        println!("from_animx_slice/{name}: {allocations} allocations, {bytes} bytes");
+    }
+}
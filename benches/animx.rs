@@ -0,0 +1,50 @@
+//! Reading and writing AnimX, on the animations from [``resonite_core::animation::fixtures``]
+//!
+//! ``cargo bench --features testing``
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use resonite_core::animation::{Animation, fixtures};
+
+fn animations() -> [(&'static str, Animation); 3] {
+    [
+        ("long_curve", fixtures::long_curve(100_000)),
+        ("many_tracks", fixtures::many_tracks(2000, 10)),
+        ("matrix_track", fixtures::matrix_track(10_000)),
+    ]
+}
+
+fn write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write");
+    for (name, anim) in animations() {
+        let len = anim.estimate_animx_size();
+        group.throughput(Throughput::Bytes(len));
+        group.bench_with_input(BenchmarkId::new("write_animx", name), &anim, |b, anim| {
+            let mut buf = Vec::with_capacity(len as usize);
+            b.iter(|| {
+                buf.clear();
+                anim.write_animx(&mut buf).unwrap()
+            });
+        });
+    }
+    group.finish();
+}
+
+fn read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read");
+    for (name, anim) in animations() {
+        let buf = anim.to_animx_bytes().unwrap();
+        group.throughput(Throughput::Bytes(buf.len() as u64));
+        group.bench_with_input(BenchmarkId::new("from_animx", name), &buf, |b, buf| {
+            b.iter(|| Animation::from_animx(black_box(&buf[..])).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("from_animx_slice", name), &buf, |b, buf| {
+            b.iter(|| Animation::from_animx_slice(black_box(buf)).unwrap());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, write, read);
+criterion_main!(benches);
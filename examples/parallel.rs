@@ -0,0 +1,44 @@
+//! Compares [``Animation::from_animx_slice``] with [``Animation::from_animx_parallel``] on a file with many tracks
+//!
+//! ``cargo run --release --example parallel --features rayon -- [tracks] [keyframes]``
+
+use std::time::Instant;
+
+use resonite_core::animation::{Animation, CurveKeyframe, DiscreteKeyframe, Interpolation, ReadOptions, types::Float3};
+
+fn main() {
+    let mut args = std::env::args().skip(1).map(|arg| arg.parse::<usize>().expect("arguments have to be numbers"));
+    let tracks = args.next().unwrap_or(512);
+    let keyframes = args.next().unwrap_or(4096);
+
+    let mut builder = Animation::builder();
+    for i in 0..tracks {
+        let times = (0..keyframes).map(|k| k as f32 / 30.0);
+        builder = match i % 3 {
+            0 => builder.add_raw_track(format!("Node{i}"), "Position", 1.0 / 30.0, times.map(|t| Float3::new(t, 0.0, 0.0)).collect()),
+            1 => builder.add_discrete_track(format!("Node{i}"), "Scale", times.map(|time| DiscreteKeyframe { time, value: time }).collect()),
+            _ => builder.add_curve_track(format!("Node{i}"), "Rotation", times.map(|time| CurveKeyframe {
+                time,
+                value: Float3::new(0.0, time, 0.0),
+                interpolation: Interpolation::Linear,
+                left_tangent: None,
+                right_tangent: None,
+            }).collect()),
+        };
+    }
+    let buf = builder.finish().to_animx_bytes().unwrap();
+    println!("{tracks} tracks of {keyframes} keyframes, {} KiB, {} threads", buf.len() / 1024, rayon::current_num_threads());
+
+    let runs = 10;
+    let start = Instant::now();
+    for _ in 0..runs {
+        Animation::from_animx_slice(&buf).unwrap();
+    }
+    println!("serial:   {:?}", start.elapsed() / runs);
+
+    let start = Instant::now();
+    for _ in 0..runs {
+        Animation::from_animx_parallel(&buf, &ReadOptions::default()).unwrap();
+    }
+    println!("parallel: {:?}", start.elapsed() / runs);
+}
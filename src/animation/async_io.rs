@@ -0,0 +1,169 @@
+//! Reading and writing AnimX with tokio's ``AsyncRead`` & ``AsyncWrite``
+//!
+//! Only available with the ``tokio`` feature
+
+use std::io::Read;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::{AnimXEncoding, AnimXError, AnimXHeader, AnimXReader, AnimXWriter, Animation, PREALLOCATION_LIMIT, ReadOptions, WriteOptions, track_len};
+
+/// Bytes gathered before they're handed to the writer
+const WRITE_CHUNK: usize = 64 << 10;
+
+impl Animation {
+    /// Same as [``Animation::from_animx``], but for async readers
+    ///
+    /// Plain streams are read a track at a time, only the track that's being read is kept in memory.
+    /// Decoding doesn't wait on anything, so it happens right on the task.\
+    /// The decompressors aren't async, so compressed streams are read to the end before they're decoded.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, CurveKeyframe};
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let anim = Animation::builder()
+    ///     .add_curve_track("n", "p", vec![CurveKeyframe::linear(0.0, 1.0f32), CurveKeyframe::linear(1.0, 2.0)])
+    ///     .add_raw_track("n", "q", 0.1, vec![true, false, true])
+    ///     .finish();
+    ///
+    /// // Small enough that neither end can get ahead of the other
+    /// let (mut writer, reader) = tokio::io::duplex(16);
+    /// let write = async {
+    ///     anim.write_animx_async(&mut writer).await.unwrap();
+    ///     drop(writer);
+    /// };
+    /// let (_, read) = tokio::join!(write, Animation::from_animx_async(reader));
+    /// assert!(read.unwrap().bit_identical(&anim));
+    /// # });
+    /// ```
+    pub async fn from_animx_async(data: impl AsyncRead + Unpin) -> Result<Animation, AnimXError> {
+        Self::from_animx_async_with_options(data, &ReadOptions::default()).await
+    }
+
+    /// Same as [``Animation::from_animx_async``], but with [``ReadOptions``] to control how lenient the reader is
+    pub async fn from_animx_async_with_options(data: impl AsyncRead + Unpin, options: &ReadOptions) -> Result<Animation, AnimXError> {
+        let mut stream = AsyncBuffer { inner: data, buf: Vec::new(), start: 0, position: 0 };
+        let (header, len) = stream.retry(|bytes| {
+            let mut reader = AnimXReader { inner: bytes, options: options.clone(), position: 0 };
+            Ok((AnimXHeader::read(&mut reader)?, bytes.len() - reader.inner.len()))
+        }).await?;
+        stream.consume(len);
+
+        let mut output = Animation {
+            name: header.name,
+            global_duration: header.global_duration,
+            tracks: Vec::with_capacity(header.track_count.min(PREALLOCATION_LIMIT)),
+            extra: serde_json::Map::new(),
+        };
+
+        if header.encoding != AnimXEncoding::Plain {
+            let position = stream.position;
+            let tracks = stream.read_to_end().await?;
+            let mut reader = AnimXReader { inner: header.encoding.decoder(&tracks[..], options)?, options: options.clone(), position };
+            for _ in 0..header.track_count {
+                output.tracks.push(Self::read_track(&mut reader)?);
+            }
+            if options.reject_trailing_data && reader.inner.read(&mut [0])? > 0 {
+                return Err(AnimXError::TrailingData);
+            }
+        } else {
+            for _ in 0..header.track_count {
+                // The length only needs the start of the track (unless it's strings), the track is read once all of it is there
+                let position = stream.position;
+                let len = stream.retry(|bytes| track_len(bytes, options, position)).await?;
+                let mut reader = AnimXReader { inner: &stream.unread()[..len], options: options.clone(), position };
+                output.tracks.push(Self::read_track(&mut reader)?);
+                stream.consume(len);
+            }
+            if options.reject_trailing_data && (!stream.unread().is_empty() || stream.fill().await?) {
+                return Err(AnimXError::TrailingData);
+            }
+        }
+        output.handle_unsorted(options)?;
+
+        Ok(output)
+    }
+
+    /// Same as [``Animation::write_animx``], but for async writers, returns the amount of bytes written
+    ///
+    /// Plain streams are encoded a few tracks at a time, compressed streams are compressed in full before they're written.
+    /// The writer is flushed at the end.
+    pub async fn write_animx_async(&self, buf: impl AsyncWrite + Unpin) -> Result<u64, AnimXError> {
+        self.write_animx_async_with_options(buf, &WriteOptions::default()).await
+    }
+
+    /// Same as [``Animation::write_animx_async``], but with [``WriteOptions``] to control details of the encoding
+    pub async fn write_animx_async_with_options(&self, mut buf: impl AsyncWrite + Unpin, options: &WriteOptions) -> Result<u64, AnimXError> {
+        let mut chunk = Vec::new();
+        if options.encoding != AnimXEncoding::Plain {
+            let written = self.write_animx_with_options(&mut chunk, options)?;
+            buf.write_all(&chunk).await?;
+            buf.flush().await?;
+            return Ok(written);
+        }
+
+        let mut written = 0;
+        self.write_header(&mut AnimXWriter { inner: &mut chunk, options, written: 0 })?;
+        for track in &self.tracks {
+            track.write(&mut AnimXWriter { inner: &mut chunk, options, written: 0 })?;
+            if chunk.len() >= WRITE_CHUNK {
+                buf.write_all(&chunk).await?;
+                written += chunk.len() as u64;
+                chunk.clear();
+            }
+        }
+        buf.write_all(&chunk).await?;
+        written += chunk.len() as u64;
+        buf.flush().await?;
+
+        Ok(written)
+    }
+}
+
+/// The bytes read from ``inner`` that haven't been decoded yet
+struct AsyncBuffer<R> {
+    inner: R,
+    buf: Vec<u8>,
+    /// Where the unread bytes start in ``buf``
+    start: usize,
+    /// Bytes consumed so far, for the offsets in errors
+    position: u64,
+}
+
+impl<R: AsyncRead + Unpin> AsyncBuffer<R> {
+    fn unread(&self) -> &[u8] {
+        &self.buf[self.start..]
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.start += len;
+        self.position += len as u64;
+    }
+
+    /// Reads whatever is available, ``false`` if the stream has ended
+    async fn fill(&mut self) -> Result<bool, AnimXError> {
+        self.buf.drain(..self.start);
+        self.start = 0;
+        self.buf.reserve(8 << 10);
+        Ok(self.inner.read_buf(&mut self.buf).await? > 0)
+    }
+
+    /// Calls ``f`` on the unread bytes until it stops running out of them, reading more in between
+    async fn retry<T>(&mut self, mut f: impl FnMut(&[u8]) -> Result<T, AnimXError>) -> Result<T, AnimXError> {
+        loop {
+            match f(self.unread()) {
+                Err(AnimXError::UnexpectedEof(len)) => if !self.fill().await? {
+                    return Err(AnimXError::UnexpectedEof(len));
+                },
+                result => return result,
+            }
+        }
+    }
+
+    async fn read_to_end(&mut self) -> Result<Vec<u8>, AnimXError> {
+        let mut rest = self.unread().to_vec();
+        self.inner.read_to_end(&mut rest).await?;
+        Ok(rest)
+    }
+}
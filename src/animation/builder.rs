@@ -0,0 +1,140 @@
+//! Building animations in code instead of deserializing them
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use super::{Animation, BezierData, BezierKeyframe, CurveData, CurveKeyframe, DiscreteData, DiscreteKeyframe, RawData, Track, types::{TrackTrait, TrackType, ValueTrait, ValueType}};
+
+/// Builds an [``Animation``] piece by piece, see [``Animation::builder``]
+#[derive(Debug)]
+pub struct AnimationBuilder {
+    name: Option<String>,
+    global_duration: Option<f32>,
+    tracks: Vec<Box<dyn TrackTrait>>,
+    auto_duration: bool,
+}
+
+impl Animation {
+    /// Starts building an animation, nothing is set and ``global_duration`` will be computed from the keyframes unless given
+    pub fn builder() -> AnimationBuilder {
+        AnimationBuilder {
+            name: None,
+            global_duration: None,
+            tracks: Vec::new(),
+            auto_duration: true,
+        }
+    }
+}
+
+impl AnimationBuilder {
+    /// Sets the name of the animation
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets ``global_duration``, taking priority over the computed one
+    pub fn global_duration(mut self, duration: f32) -> Self {
+        self.global_duration = Some(duration);
+        self
+    }
+
+    /// Adds a track to the animation
+    pub fn track(mut self, track: impl TrackTrait + 'static) -> Self {
+        self.tracks.push(Box::new(track));
+        self
+    }
+
+    /// Adds a raw track with a frame every ``interval`` seconds
+    ///
+    /// The value type comes from ``T``, 4 component vectors end up as ``float4``/``double4``, use [``AnimationBuilder::track``] for rotations.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, CurveKeyframe, RawData, Track, types::Float3};
+    ///
+    /// let anim = Animation::builder()
+    ///     .name("Bounce")
+    ///     .add_raw_track("Ball", "Position", 0.5, vec![Float3::new(0.0, 0.0, 0.0), Float3::new(0.0, 1.0, 0.0)])
+    ///     .add_curve_track("Ball", "Squash", vec![CurveKeyframe::linear(0.0, 1.0f32), CurveKeyframe::hold(0.75, 0.5)])
+    ///     .finish();
+    ///
+    /// let read = Animation::from_animx(&anim.to_animx_bytes().unwrap()[..]).unwrap();
+    /// assert!(read.bit_identical(&anim));
+    /// assert_eq!(read.name.as_deref(), Some("Bounce"));
+    /// assert_eq!(read.global_duration, Some(0.75));
+    /// assert_eq!(read.tracks[0].downcast::<Track<RawData<Float3>>>().unwrap().data.keyframes[1].y, 1.0);
+    /// ```
+    #[allow(private_bounds)]
+    pub fn add_raw_track<T>(self, node: impl Into<String>, property: impl Into<String>, interval: f32, keyframes: Vec<T>) -> Self where T: ValueTrait {
+        self.track(Track { track_type: TrackType::Raw, value_type: ValueType::of::<T>(), data: RawData {
+            node: Some(node.into()),
+            property: Some(property.into()),
+            interval: Some(interval),
+            keyframes,
+        }})
+    }
+
+    /// Adds a discrete track, see [``AnimationBuilder::add_raw_track``] for the value type
+    #[allow(private_bounds)]
+    pub fn add_discrete_track<T>(self, node: impl Into<String>, property: impl Into<String>, keyframes: Vec<DiscreteKeyframe<T>>) -> Self where T: ValueTrait {
+        self.track(Track { track_type: TrackType::Discrete, value_type: ValueType::of::<T>(), data: DiscreteData {
+            node: Some(node.into()),
+            property: Some(property.into()),
+            keyframes,
+        }})
+    }
+
+    /// Adds a curve track, see [``AnimationBuilder::add_raw_track``] for the value type
+    #[allow(private_bounds)]
+    pub fn add_curve_track<T>(self, node: impl Into<String>, property: impl Into<String>, keyframes: Vec<CurveKeyframe<T>>) -> Self where T: ValueTrait {
+        self.track(Track { track_type: TrackType::Curve, value_type: ValueType::of::<T>(), data: CurveData {
+            node: Some(node.into()),
+            property: Some(property.into()),
+            keyframes,
+        }})
+    }
+
+    /// Adds a bezier track, see [``AnimationBuilder::add_raw_track``] for the value type
+    #[allow(private_bounds)]
+    pub fn add_bezier_track<T>(self, node: impl Into<String>, property: impl Into<String>, keyframes: Vec<BezierKeyframe<T>>) -> Self where T: ValueTrait {
+        self.track(Track { track_type: TrackType::Bezier, value_type: ValueType::of::<T>(), data: BezierData {
+            node: Some(node.into()),
+            property: Some(property.into()),
+            keyframes,
+        }})
+    }
+
+    /// Whether [``AnimationBuilder::finish``] sets a missing ``global_duration`` to [``Animation::computed_duration``], on by default\
+    /// Without it an animation built without a duration is zero seconds long in Resonite
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, DiscreteData, Track, types::{Float, TrackType, ValueType}};
+    ///
+    /// let track = Track { track_type: TrackType::Discrete, value_type: ValueType::Float, data: DiscreteData::<Float> {
+    ///     node: Some("n".into()), property: Some("p".into()), keyframes: vec![(0.0, 1.0).into(), (2.5, 3.0).into()],
+    /// }};
+    ///
+    /// let anim = Animation::builder().track(track.clone()).finish();
+    /// assert_eq!(anim.global_duration, Some(2.5));
+    ///
+    /// let anim = Animation::builder().track(track).auto_duration(false).finish();
+    /// assert_eq!(anim.global_duration, None);
+    /// ```
+    pub fn auto_duration(mut self, enabled: bool) -> Self {
+        self.auto_duration = enabled;
+        self
+    }
+
+    /// Makes the animation
+    pub fn finish(self) -> Animation {
+        let mut output = Animation {
+            name: self.name,
+            global_duration: self.global_duration,
+            tracks: self.tracks,
+            extra: serde_json::Map::new(),
+        };
+        if output.global_duration.is_none() && self.auto_duration {
+            output.global_duration = Some(output.computed_duration());
+        }
+        output
+    }
+}
@@ -0,0 +1,97 @@
+//! Comparing animations with some room for floating point error, see [``Animation::approx_eq``]
+
+use super::{Animation, BezierData, CurveData, DiscreteData, RawData, types::ValueTrait};
+
+impl Animation {
+    /// Whether both animations are the same, give or take ``epsilon`` on every number
+    ///
+    /// The name, the unknown AnimJ fields, the track count and order, and the type, node and property of every track have to match exactly,
+    /// as do keyframe counts and curve interpolations.
+    /// Times, durations, intervals and values only have to be within ``epsilon``, vectors & matrices by their euclidean distance.\
+    /// Integers are compared as numbers too, bools and strings have to be equal. NaN is never close to anything.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, CurveKeyframe};
+    ///
+    /// let anim = |offset: f32| Animation::builder()
+    ///     .add_curve_track("n", "p", vec![CurveKeyframe::linear(0.0, 1.0f32), CurveKeyframe::linear(1.0, 2.0 + offset)])
+    ///     .add_raw_track("n", "q", 0.1, vec![true, false])
+    ///     .finish();
+    ///
+    /// assert!(anim(0.0).approx_eq(&anim(1e-7), 1e-5));
+    /// assert!(!anim(0.0).approx_eq(&anim(0.1), 1e-5));
+    /// assert!(anim(0.0).approx_eq(&anim(0.1), 0.5));
+    ///
+    /// // Structure isn't up to epsilon
+    /// let mut renamed = anim(0.0);
+    /// renamed.rename_node("n", "m");
+    /// assert!(!anim(0.0).approx_eq(&renamed, 1.0));
+    /// ```
+    pub fn approx_eq(&self, other: &Animation, epsilon: f32) -> bool {
+        self.name == other.name
+            && self.extra == other.extra
+            && option_eq(self.global_duration, other.global_duration, epsilon)
+            && self.tracks.len() == other.tracks.len()
+            && self.tracks.iter().zip(&other.tracks).all(|(a, b)| a.approx_eq(b.as_ref(), epsilon))
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> RawData<T> where T: ValueTrait {
+    pub(crate) fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        option_eq(self.interval, other.interval, epsilon)
+            && self.keyframes.len() == other.keyframes.len()
+            && self.keyframes.iter().zip(&other.keyframes).all(|(a, b)| a.approx_eq(b, epsilon))
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> DiscreteData<T> where T: ValueTrait {
+    pub(crate) fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.keyframes.len() == other.keyframes.len()
+            && self.keyframes.iter().zip(&other.keyframes).all(|(a, b)| time_eq(a.time, b.time, epsilon) && a.value.approx_eq(&b.value, epsilon))
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> CurveData<T> where T: ValueTrait {
+    pub(crate) fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        let tangent_eq = |a: &Option<(f32, T)>, b: &Option<(f32, T)>| match (a, b) {
+            (Some((a_time, a)), Some((b_time, b))) => time_eq(*a_time, *b_time, epsilon) && a.approx_eq(b, epsilon),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+
+        self.keyframes.len() == other.keyframes.len()
+            && self.keyframes.iter().zip(&other.keyframes).all(|(a, b)| {
+                a.interpolation == b.interpolation
+                    && time_eq(a.time, b.time, epsilon)
+                    && a.value.approx_eq(&b.value, epsilon)
+                    && tangent_eq(&a.left_tangent, &b.left_tangent)
+                    && tangent_eq(&a.right_tangent, &b.right_tangent)
+            })
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> BezierData<T> where T: ValueTrait {
+    pub(crate) fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.keyframes.len() == other.keyframes.len()
+            && self.keyframes.iter().zip(&other.keyframes).all(|(a, b)| {
+                time_eq(a.time, b.time, epsilon)
+                    && a.value.approx_eq(&b.value, epsilon)
+                    && a.left_control.approx_eq(&b.left_control, epsilon)
+                    && a.right_control.approx_eq(&b.right_control, epsilon)
+            })
+    }
+}
+
+fn time_eq(a: f32, b: f32, epsilon: f32) -> bool {
+    (a - b).abs() <= epsilon
+}
+
+fn option_eq(a: Option<f32>, b: Option<f32>, epsilon: f32) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => time_eq(a, b, epsilon),
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
@@ -0,0 +1,84 @@
+//! Compressed AnimX streams, only available with the ``compression`` feature
+
+use std::io::{BufReader, Cursor, Read, Write};
+
+use super::{AnimXEncoding, AnimXError};
+
+/// Backs [``AnimXEncoding::decoder``] for the compressed encodings, the tracks can't decompress to more than ``max_len`` bytes
+pub(super) fn decoder<'a>(encoding: AnimXEncoding, inner: impl Read + 'a, max_len: usize) -> Result<Box<dyn Read + 'a>, AnimXError> {
+    match encoding {
+        AnimXEncoding::Plain => Ok(Box::new(inner)),
+        AnimXEncoding::Deflate => Ok(Box::new(Limited::new(flate2::read::DeflateDecoder::new(inner), max_len))),
+        AnimXEncoding::Lzma => {
+            // lzma-rs can't be read from bit by bit, so everything gets decompressed up front
+            let mut tracks = Limited::new(Vec::new(), max_len);
+            lzma_rs::lzma_decompress(&mut BufReader::new(inner), &mut tracks).map_err(|e| match e {
+                lzma_rs::error::Error::IoError(e) => AnimXError::IoError(e),
+                e => AnimXError::IoError(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            })?;
+            Ok(Box::new(Cursor::new(tracks.inner)))
+        },
+    }
+}
+
+/// Reads or writes through ``inner``, erroring once more than ``limit`` bytes went through
+struct Limited<T> {
+    inner: T,
+    len: usize,
+    limit: usize,
+}
+
+impl<T> Limited<T> {
+    fn new(inner: T, limit: usize) -> Self {
+        Self { inner, len: 0, limit }
+    }
+
+    fn count(&mut self, len: usize) -> std::io::Result<usize> {
+        self.len += len;
+        if self.len > self.limit {
+            let message = format!("the tracks decompress to more than {} bytes, see ReadOptions::max_decompressed_len", self.limit);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, message));
+        }
+        Ok(len)
+    }
+}
+
+impl<T> Read for Limited<T> where T: Read {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.count(len)
+    }
+}
+
+impl<T> Write for Limited<T> where T: Write {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Counted before writing, so the output never grows past the limit
+        self.count(buf.len())?;
+        self.inner.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Compresses the tracks of a stream for [``WriteOptions::encoding``](super::WriteOptions::encoding)
+pub(super) fn compress(encoding: AnimXEncoding, tracks: &[u8]) -> std::io::Result<Vec<u8>> {
+    match encoding {
+        AnimXEncoding::Plain => Ok(tracks.to_vec()),
+        AnimXEncoding::Deflate => {
+            let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(tracks)?;
+            encoder.finish()
+        },
+        AnimXEncoding::Lzma => {
+            let mut output = Vec::new();
+            let options = lzma_rs::compress::Options {
+                unpacked_size: lzma_rs::compress::UnpackedSize::WriteToHeader(Some(tracks.len() as u64)),
+            };
+            lzma_rs::lzma_compress_with_options(&mut BufReader::new(tracks), &mut output, &options)?;
+            Ok(output)
+        },
+    }
+}
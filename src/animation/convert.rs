@@ -0,0 +1,254 @@
+//! Conversions between the ways a track can be represented
+
+use alloc::boxed::Box;
+
+use super::{Animation, BezierData, BezierKeyframe, CurveData, CurveKeyframe, DiscreteData, DiscreteKeyframe, Interpolation, RawData, Track, types::*};
+
+#[allow(private_bounds)]
+impl<T> CurveData<T> where T: ValueTrait {
+    /// Converts the curve to bezier segments with the same shape
+    ///
+    /// Only the slope of the tangents matters, the control points end up a third of a segment away along them.\
+    /// Keyframes without tangents get control points on the straight line to their neighbours, so ``Hold`` keyframes turn linear.
+    ///
+    /// ```
+    /// use resonite_core::animation::{CurveData, CurveKeyframe, Interpolation};
+    ///
+    /// let keyframe = |time: f32, value: f32, left: f32, right: f32| CurveKeyframe {
+    ///     time, value, interpolation: Interpolation::Tangent, left_tangent: Some((-1.0, -left)), right_tangent: Some((1.0, right)),
+    /// };
+    /// let curve = CurveData { node: None, property: None, keyframes: vec![
+    ///     keyframe(0.0, 0.0, 0.0, 0.5),
+    ///     keyframe(2.0, 1.0, 3.0, 3.0),
+    ///     keyframe(3.0, 0.0, -1.0, 0.0),
+    /// ]};
+    ///
+    /// let bezier = curve.to_bezier();
+    /// assert_eq!(bezier.keyframes[1].left_control, -1.0);
+    /// assert_eq!(bezier.keyframes[1].right_control, 2.0);
+    ///
+    /// let slope = |(time, value): (f32, f32)| if time == 0.0 { 0.0 } else { value / time };
+    /// let back = bezier.to_curve();
+    /// assert_eq!(back.keyframes[1].left_tangent, Some((-2.0 / 3.0, -2.0)));
+    /// for (a, b) in curve.keyframes.iter().zip(&back.keyframes) {
+    ///     assert!((slope(a.left_tangent.unwrap()) - slope(b.left_tangent.unwrap())).abs() < 1e-5);
+    ///     assert!((slope(a.right_tangent.unwrap()) - slope(b.right_tangent.unwrap())).abs() < 1e-5);
+    /// }
+    /// ```
+    pub fn to_bezier(&self) -> BezierData<T> {
+        let keyframes = (0..self.keyframes.len()).map(|i| {
+            let k = &self.keyframes[i];
+            let previous = i.checked_sub(1).and_then(|i| self.keyframes.get(i));
+            let next = self.keyframes.get(i + 1);
+
+            let left_control = match (previous, &k.left_tangent) {
+                (Some(p), Some(tangent)) => along(&k.value, tangent, -(k.time - p.time) / 3.0),
+                (Some(p), None) => k.value.lerp(&p.value, 1.0 / 3.0),
+                (None, _) => k.value.clone(),
+            };
+            let right_control = match (next, &k.right_tangent) {
+                (Some(n), Some(tangent)) => along(&k.value, tangent, (n.time - k.time) / 3.0),
+                (Some(n), None) => k.value.lerp(&n.value, 1.0 / 3.0),
+                (None, _) => k.value.clone(),
+            };
+
+            BezierKeyframe { time: k.time, value: k.value.clone(), left_control, right_control }
+        }).collect();
+
+        BezierData { node: self.node.clone(), property: self.property.clone(), keyframes }
+    }
+}
+
+/// Point ``dt`` seconds along a tangent, tangents with a time of zero are flat
+pub(super) fn along<T>(value: &T, (time, tangent): &(f32, T), dt: f32) -> T where T: ValueTrait {
+    if *time == 0.0 { value.clone() } else { value.add_scaled(tangent, dt / time) }
+}
+
+#[allow(private_bounds)]
+impl<T> BezierData<T> where T: ValueTrait {
+    /// Converts the bezier segments to a curve of ``Tangent`` keyframes with the same shape, the inverse of [``CurveData::to_bezier``]
+    ///
+    /// The tangents point at the control points, a third of a segment away.\
+    /// The first and last keyframe have no neighbour on one side, their tangent on that side is flat with a time of zero
+    pub fn to_curve(&self) -> CurveData<T> {
+        let tangent = |k: &BezierKeyframe<T>, control: &T, dt: f32| (dt / 3.0, control.add_scaled(&k.value, -1.0));
+
+        let keyframes = (0..self.keyframes.len()).map(|i| {
+            let k = &self.keyframes[i];
+            let previous = i.checked_sub(1).and_then(|i| self.keyframes.get(i)).map_or(0.0, |p| k.time - p.time);
+            let next = self.keyframes.get(i + 1).map_or(0.0, |n| n.time - k.time);
+
+            CurveKeyframe {
+                time: k.time,
+                value: k.value.clone(),
+                interpolation: Interpolation::Tangent,
+                // The left control is behind the keyframe, so its time is negative
+                left_tangent: Some(tangent(k, &k.left_control, -previous)),
+                right_tangent: Some(tangent(k, &k.right_control, next)),
+            }
+        }).collect();
+
+        CurveData { node: self.node.clone(), property: self.property.clone(), keyframes }
+    }
+}
+
+impl Animation {
+    /// Converts the value type of every track ``f`` gives a new type for, see [``TrackTrait::convert_value_type``]\
+    /// Tracks that can't be converted to the type they're mapped to are left untouched
+    pub fn map_value_type(&mut self, f: impl Fn(ValueType) -> Option<ValueType>) {
+        for track in &mut self.tracks {
+            if let Some(value_type) = f(track.value_type()) && let Some(converted) = track.convert_value_type(value_type) {
+                *track = converted;
+            }
+        }
+    }
+
+    /// Converts every ``Float*`` track to its ``Double*`` counterpart, leaving other tracks untouched
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, Track, DiscreteData, types::*};
+    ///
+    /// let mut anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Discrete", "valueType": "float3", "data": {"node": "n", "property": "a", "keyframes": [
+    ///         {"time": 0.0, "value": {"x": 0.1, "y": 0.2, "z": 0.3}}
+    ///     ]}},
+    ///     {"trackType": "Raw", "valueType": "float3", "data": {"node": "n", "property": "b", "interval": 0.5, "keyframes": [
+    ///         {"x": 1.5, "y": 2.5, "z": 3.5}
+    ///     ]}}
+    /// ]}"#).unwrap();
+    ///
+    /// anim.promote_to_double();
+    /// assert!(anim.tracks.iter().all(|t| t.value_type() == ValueType::Double3));
+    /// let track = anim.tracks[0].as_any().downcast_ref::<Track<DiscreteData<Double3>>>().unwrap();
+    /// assert!((track.data.keyframes[0].value.y - 0.2).abs() < 1e-6);
+    ///
+    /// anim.demote_to_float();
+    /// assert!(anim.tracks.iter().all(|t| t.value_type() == ValueType::Float3));
+    /// let track = anim.tracks[0].as_any().downcast_ref::<Track<DiscreteData<Float3>>>().unwrap();
+    /// assert!((track.data.keyframes[0].value.y - 0.2).abs() < 1e-6);
+    /// ```
+    pub fn promote_to_double(&mut self) {
+        self.map_value_type(|value_type| PRECISION_PAIRS.iter().find(|(float, _)| *float == value_type).map(|(_, double)| *double));
+    }
+
+    /// Converts every ``Double*`` track to its ``Float*`` counterpart, leaving other tracks untouched\
+    /// This loses precision, see [``Animation::promote_to_double``]
+    pub fn demote_to_float(&mut self) {
+        self.map_value_type(|value_type| PRECISION_PAIRS.iter().find(|(_, double)| *double == value_type).map(|(float, _)| *float));
+    }
+}
+
+metamatch::quote! {
+    [<let pairs = [
+        (Float, Double), (Float2, Double2), (Float3, Double3), (Float4, Double4),
+        (FloatQ, DoubleQ), (Float2x2, Double2x2), (Float3x3, Double3x3), (Float4x4, Double4x4),
+    ]>]
+
+    /// The float value types and their double counterparts
+    const PRECISION_PAIRS: [(ValueType, ValueType); 8] = [
+        [<for (float, double) in pairs>]
+            (ValueType::[<ident(str(float))>], ValueType::[<ident(str(double))>]),
+        [</for>]
+    ];
+
+    /// Backs [``TrackTrait::convert_value_type``]
+    pub(super) fn convert_value_type(track: &dyn TrackTrait, value_type: ValueType) -> Option<Box<dyn TrackTrait>> {
+        match (track.value_type(), value_type) {
+            [<for (float, double) in pairs>]
+                (ValueType::[<ident(str(float))>], ValueType::[<ident(str(double))>]) => convert_track::<[<ident(str(float))>], [<ident(str(double))>]>(track, value_type),
+                (ValueType::[<ident(str(double))>], ValueType::[<ident(str(float))>]) => convert_track::<[<ident(str(double))>], [<ident(str(float))>]>(track, value_type),
+            [</for>]
+            _ => None,
+        }
+    }
+}
+
+/// Makes a copy of ``track`` with every value converted from ``F`` to ``D``
+fn convert_track<F, D>(track: &dyn TrackTrait, value_type: ValueType) -> Option<Box<dyn TrackTrait>>
+    where F: ValueTrait + Precision<D>, D: ValueTrait
+{
+    let any = track.as_any();
+    if let Some(track) = any.downcast_ref::<Track<RawData<F>>>() {
+        let data = &track.data;
+        Some(Box::new(Track { track_type: track.track_type, value_type, data: RawData {
+            node: data.node.clone(),
+            property: data.property.clone(),
+            interval: data.interval,
+            keyframes: data.keyframes.iter().map(Precision::convert).collect(),
+        }}))
+    } else if let Some(track) = any.downcast_ref::<Track<DiscreteData<F>>>() {
+        let data = &track.data;
+        Some(Box::new(Track { track_type: track.track_type, value_type, data: DiscreteData {
+            node: data.node.clone(),
+            property: data.property.clone(),
+            keyframes: data.keyframes.iter().map(|k| DiscreteKeyframe { time: k.time, value: k.value.convert() }).collect(),
+        }}))
+    } else if let Some(track) = any.downcast_ref::<Track<CurveData<F>>>() {
+        let data = &track.data;
+        Some(Box::new(Track { track_type: track.track_type, value_type, data: CurveData {
+            node: data.node.clone(),
+            property: data.property.clone(),
+            keyframes: data.keyframes.iter().map(|k| CurveKeyframe {
+                time: k.time,
+                value: k.value.convert(),
+                interpolation: k.interpolation,
+                left_tangent: k.left_tangent.as_ref().map(|(time, t)| (*time, t.convert())),
+                right_tangent: k.right_tangent.as_ref().map(|(time, t)| (*time, t.convert())),
+            }).collect(),
+        }}))
+    } else if let Some(track) = any.downcast_ref::<Track<BezierData<F>>>() {
+        let data = &track.data;
+        Some(Box::new(Track { track_type: track.track_type, value_type, data: BezierData {
+            node: data.node.clone(),
+            property: data.property.clone(),
+            keyframes: data.keyframes.iter().map(|k| BezierKeyframe {
+                time: k.time,
+                value: k.value.convert(),
+                left_control: k.left_control.convert(),
+                right_control: k.right_control.convert(),
+            }).collect(),
+        }}))
+    } else {
+        None
+    }
+}
+
+/// Conversion between the float and double version of a value
+trait Precision<U> {
+    fn convert(&self) -> U;
+}
+
+impl Precision<Double> for Float {
+    fn convert(&self) -> Double {
+        *self as Double
+    }
+}
+
+impl Precision<Float> for Double {
+    fn convert(&self) -> Float {
+        *self as Float
+    }
+}
+
+metamatch::quote! {
+    [<for (from, to) in [(Float, Double), (Double, Float)]>]
+        [<for (size, fields) in [(2, [x,y]), (3, [x,y,z]), (4, [x,y,z,w])]>]
+            impl Precision<[<ident(str(to) + str(size))>]> for [<ident(str(from) + str(size))>] {
+                fn convert(&self) -> [<ident(str(to) + str(size))>] {
+                    [<ident(str(to) + str(size))>] {
+                        [<for field in fields>]
+                            [<ident(str(field))>]: self.[<ident(str(field))>].convert(),
+                        [</for>]
+                    }
+                }
+            }
+        [</for>]
+    [</for>]
+}
+
+/// Matrices are just nested arrays
+impl<A, B, const N: usize> Precision<[B; N]> for [A; N] where A: Precision<B> {
+    fn convert(&self) -> [B; N] {
+        core::array::from_fn(|i| self[i].convert())
+    }
+}
@@ -0,0 +1,168 @@
+//! CSV export of keyframes
+//!
+//! Only available with the ``csv`` feature
+
+use std::io::Write;
+
+use super::{Animation, BezierData, CurveData, DiscreteData, RawData, Track, types::*};
+
+/// The time & value columns of every keyframe of a track
+type Rows = (TrackType, ValueType, Vec<(f32, Vec<String>)>);
+
+impl Animation {
+    /// Writes every keyframe as a CSV row of ``node, property, track_type, value_type, time, v0, v1, v2, v3``
+    ///
+    /// Values are split into a column per component (``x, y, z, w`` or ``r, g, b, a``), matrices go row by row and add more columns
+    /// (``v4`` onwards) if the animation has any. Bools are written as ``true``/``false`` and strings as a single column,
+    /// columns a value doesn't use are left empty. Raw frames get the time ``index * interval``.
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Discrete", "valueType": "float3", "data": {"node": "n", "property": "Position", "keyframes": [
+    ///         {"time": 0.5, "value": {"x": 1.0, "y": 2.5, "z": 3.0}}
+    ///     ]}},
+    ///     {"trackType": "Raw", "valueType": "string", "data": {"node": "n", "property": "Text", "interval": 0.25, "keyframes": ["a", "b, c"]}}
+    /// ]}"#).unwrap();
+    ///
+    /// let mut buf = Vec::new();
+    /// anim.write_csv(&mut buf).unwrap();
+    /// assert_eq!(String::from_utf8(buf).unwrap(), "\
+    /// node,property,track_type,value_type,time,v0,v1,v2,v3
+    /// n,Position,Discrete,float3,0.5,1,2.5,3,
+    /// n,Text,Raw,string,0,a,,,
+    /// n,Text,Raw,string,0.25,\"b, c\",,,
+    /// ");
+    /// ```
+    pub fn write_csv(&self, writer: impl Write) -> csv::Result<()> {
+        let tracks: Vec<_> = self.tracks.iter().map(|track| (track, rows_of(track.as_ref()))).collect();
+        let width = tracks.iter()
+            .flat_map(|(_, rows)| rows.iter().flat_map(|(_, _, rows)| rows.first()))
+            .map(|(_, fields)| fields.len())
+            .fold(4, usize::max);
+
+        let mut writer = csv::Writer::from_writer(writer);
+        let mut header = vec!["node".to_owned(), "property".to_owned(), "track_type".to_owned(), "value_type".to_owned(), "time".to_owned()];
+        header.extend((0..width).map(|i| format!("v{i}")));
+        writer.write_record(&header)?;
+
+        for (track, rows) in tracks {
+            let Some((track_type, value_type, rows)) = rows else { continue };
+            let node = track.node().unwrap_or_default();
+            let property = track.property().unwrap_or_default();
+            for (time, fields) in rows {
+                let mut record = vec![node.to_owned(), property.to_owned(), serde_name(&track_type), serde_name(&value_type), time.to_string()];
+                record.extend(fields);
+                record.resize(5 + width, String::new());
+                writer.write_record(&record)?;
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Name of a track or value type as it appears in AnimJ
+fn serde_name(value: &impl serde::Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+fn rows_of(track: &dyn TrackTrait) -> Option<Rows> {
+    // Quaternions are the same type as the 4 component vectors, so they don't need their own case
+    let any = track.as_any();
+    metamatch::quote! {
+        None
+        [<for T in [
+            Byte, Ushort, Ulong, Sbyte, Short,
+            Bool, Bool2, Bool3, Bool4,
+            Int, Int2, Int3, Int4,
+            Uint, Uint2, Uint3, Uint4,
+            Long, Long2, Long3, Long4,
+            Float, Float2, Float3, Float4,
+            Float2x2, Float3x3, Float4x4,
+            Double, Double2, Double3, Double4,
+            Double2x2, Double3x3, Double4x4,
+            Color, Color32, OptString,
+        ]>]
+            .or_else(|| rows::<[<ident(str(T))>]>(any))
+        [</for>]
+    }
+}
+
+fn rows<T>(any: &dyn std::any::Any) -> Option<Rows> where T: Fields + ValueTrait {
+    if let Some(track) = any.downcast_ref::<Track<RawData<T>>>() {
+        let interval = track.data.interval.unwrap_or_default();
+        let rows = track.data.keyframes.iter().enumerate().map(|(i, value)| (i as f32 * interval, value.fields())).collect();
+        Some((track.track_type, track.value_type, rows))
+    } else if let Some(track) = any.downcast_ref::<Track<DiscreteData<T>>>() {
+        let rows = track.data.keyframes.iter().map(|k| (k.time, k.value.fields())).collect();
+        Some((track.track_type, track.value_type, rows))
+    } else if let Some(track) = any.downcast_ref::<Track<CurveData<T>>>() {
+        let rows = track.data.keyframes.iter().map(|k| (k.time, k.value.fields())).collect();
+        Some((track.track_type, track.value_type, rows))
+    } else {
+        let track = any.downcast_ref::<Track<BezierData<T>>>()?;
+        let rows = track.data.keyframes.iter().map(|k| (k.time, k.value.fields())).collect();
+        Some((track.track_type, track.value_type, rows))
+    }
+}
+
+/// Splits a value up into its CSV columns
+trait Fields {
+    fn fields(&self) -> Vec<String>;
+}
+
+metamatch::quote! {
+    [<for T in [Byte, Ushort, Ulong, Sbyte, Short, Bool, Int, Uint, Long, Float, Double]>]
+        impl Fields for [<ident(str(T))>] {
+            fn fields(&self) -> Vec<String> {
+                vec![self.to_string()]
+            }
+        }
+    [</for>]
+}
+
+metamatch::quote! {
+    [<for name in [Bool, Int, Uint, Long, Float, Double]>]
+        [<for range in 2..5>]
+            impl Fields for [<ident(str(name) + str(range))>] {
+                fn fields(&self) -> Vec<String> {
+                    vec![
+                        [<for field in 0..range>]
+                            [<let field_name = [x,y,z,w][field]>]
+                            self.[<ident(str(field_name))>].to_string(),
+                        [</for>]
+                    ]
+                }
+            }
+        [</for>]
+    [</for>]
+}
+
+metamatch::quote! {
+    [<for name in [Color, Color32]>]
+        impl Fields for [<ident(str(name))>] {
+            fn fields(&self) -> Vec<String> {
+                vec![self.r.to_string(), self.g.to_string(), self.b.to_string(), self.a.to_string()]
+            }
+        }
+    [</for>]
+}
+
+/// Matrices are just nested arrays, so they're flattened row by row
+impl<T, const N: usize> Fields for [T; N] where T: Fields {
+    fn fields(&self) -> Vec<String> {
+        self.iter().flat_map(Fields::fields).collect()
+    }
+}
+
+impl Fields for OptString {
+    fn fields(&self) -> Vec<String> {
+        vec![self.0.clone()]
+    }
+}
@@ -0,0 +1,88 @@
+//! Removing keyframes that don't change how a track samples, see [``TrackTrait::decimate``](super::types::TrackTrait::decimate)
+
+use alloc::{vec, vec::Vec};
+
+use super::{CurveData, DiscreteData, Interpolation, types::{Blend, ValueTrait}};
+
+#[allow(private_bounds)]
+impl<T> DiscreteData<T> where T: ValueTrait {
+    /// Drops keyframes repeating the one before them, discrete tracks step so they'd change nothing
+    pub(crate) fn decimate(&mut self, tolerance: f32) -> usize {
+        let Some(last) = self.keyframes.len().checked_sub(1) else { return 0 };
+        let mut keep = vec![true; self.keyframes.len()];
+        if last > 0 {
+            drop_repeats(&self.keyframes[0].value, self.keyframes[1..last].iter().map(|k| &k.value), &mut keep[1..last], tolerance);
+        }
+
+        retain(&mut self.keyframes, &keep)
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> CurveData<T> where T: ValueTrait {
+    /// Simplifies every run of keyframes sharing an interpolation, the keyframes between runs always stay
+    pub(crate) fn decimate(&mut self, tolerance: f32, blend: Blend<T>) -> usize {
+        let len = self.keyframes.len();
+        let mut keep = vec![true; len];
+
+        let mut start = 0;
+        while start + 1 < len {
+            let interpolation = self.keyframes[start].interpolation;
+            let mut end = start + 1;
+            while end + 1 < len && self.keyframes[end].interpolation == interpolation {
+                end += 1;
+            }
+
+            match interpolation {
+                Interpolation::Hold => {
+                    let values = self.keyframes[start + 1..end].iter().map(|k| &k.value);
+                    drop_repeats(&self.keyframes[start].value, values, &mut keep[start + 1..end], tolerance);
+                },
+                Interpolation::Linear => self.simplify_line(start, end, tolerance, blend, &mut keep),
+                // Dropping these would need new tangents to keep the shape
+                Interpolation::Tangent | Interpolation::CubicBezier => {},
+            }
+            start = end;
+        }
+
+        retain(&mut self.keyframes, &keep)
+    }
+
+    /// Ramer–Douglas–Peucker between the keyframes ``start`` and ``end``, measuring how far each keyframe is from the line at its own time
+    fn simplify_line(&self, start: usize, end: usize, tolerance: f32, blend: Blend<T>, keep: &mut [bool]) {
+        let mut ranges = vec![(start, end)];
+        while let Some((start, end)) = ranges.pop() {
+            let (a, b) = (&self.keyframes[start], &self.keyframes[end]);
+            let furthest = (start + 1..end).map(|i| {
+                let k = &self.keyframes[i];
+                let t = if b.time > a.time { (k.time - a.time) / (b.time - a.time) } else { 0.0 };
+                (i, k.value.distance(&blend(&a.value, &b.value, t)))
+            }).max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            match furthest {
+                Some((i, distance)) if distance > tolerance => ranges.extend([(start, i), (i, end)]),
+                _ => keep[start + 1..end].fill(false),
+            }
+        }
+    }
+}
+
+/// Unmarks every value within ``tolerance`` of the last one that stays, starting from ``anchor``\
+/// Those repeat the value before them, which only matters for values that step
+fn drop_repeats<'a, T>(mut anchor: &'a T, values: impl Iterator<Item = &'a T>, keep: &mut [bool], tolerance: f32) where T: ValueTrait {
+    for (value, keep) in values.zip(keep) {
+        if value.distance(anchor) <= tolerance {
+            *keep = false;
+        } else {
+            anchor = value;
+        }
+    }
+}
+
+/// Keeps the keyframes marked in ``keep``, returning how many were removed
+fn retain<K>(keyframes: &mut Vec<K>, keep: &[bool]) -> usize {
+    let before = keyframes.len();
+    let mut keep = keep.iter();
+    keyframes.retain(|_| *keep.next().unwrap());
+    before - keyframes.len()
+}
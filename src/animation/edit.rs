@@ -0,0 +1,867 @@
+//! Operations for editing whole animations
+
+use alloc::{borrow::ToOwned, boxed::Box, format, string::{String, ToString}, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{Animation, BezierData, CurveData, CurveKeyframe, DiscreteData, DiscreteKeyframe, Interpolation, KeyframeTrait, RawData, Track, types::*};
+
+impl Animation {
+    /// Shifts every keyframe of every track by ``delta`` seconds, extending (or shortening) ``global_duration`` along with them
+    ///
+    /// Tracks are moved the same way as [``Animation::shift_time``] moves them, the differences are with negative ``delta``s:
+    /// - Nothing is checked, keyframes can end up before 0
+    /// - Raw tracks can't start before 0, so they stay where they are
+    /// - ``global_duration`` stops at 0
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, CurveKeyframe, DiscreteKeyframe};
+    ///
+    /// let mut anim = Animation::builder()
+    ///     .global_duration(2.0)
+    ///     .add_discrete_track("n", "a", vec![DiscreteKeyframe { time: 0.5, value: 1.0f32 }, DiscreteKeyframe { time: 2.0, value: 2.0 }])
+    ///     .add_curve_track("n", "b", vec![CurveKeyframe::linear(0.0, 0.0f32), CurveKeyframe::linear(1.0, 1.0)])
+    ///     .add_raw_track("n", "c", 0.25, vec![1.0f32, 2.0])
+    ///     .finish();
+    ///
+    /// anim.offset_all_times(1.0);
+    /// assert_eq!(anim.global_duration, Some(3.0));
+    /// assert_eq!((anim.tracks[0].start_time(), anim.tracks[0].end_time()), (1.5, 3.0));
+    /// assert_eq!((anim.tracks[1].start_time(), anim.tracks[1].end_time()), (1.0, 2.0));
+    /// assert_eq!(anim.tracks[2].end_time(), 1.25); // Four copies of the first frame in front
+    ///
+    /// anim.offset_all_times(-2.0);
+    /// assert_eq!(anim.global_duration, Some(1.0));
+    /// assert_eq!((anim.tracks[0].start_time(), anim.tracks[0].end_time()), (-0.5, 1.0));
+    /// assert_eq!((anim.tracks[1].start_time(), anim.tracks[1].end_time()), (-1.0, 0.0));
+    /// assert_eq!(anim.tracks[2].end_time(), 1.25);
+    ///
+    /// anim.offset_all_times(-5.0);
+    /// assert_eq!(anim.global_duration, Some(0.0));
+    /// ```
+    pub fn offset_all_times(&mut self, delta: f32) {
+        for track in &mut self.tracks {
+            track.shift_times(delta);
+        }
+        if let Some(duration) = &mut self.global_duration {
+            *duration = (*duration + delta).max(0.0);
+        }
+    }
+
+    /// Moves every keyframe ``delta`` seconds later (or earlier with a negative ``delta``), ``global_duration`` moves along
+    ///
+    /// Unlike [``Animation::offset_all_times``] nothing can end up before 0, if a keyframe would, the animation is left untouched
+    /// and the error has the index of its track.\
+    /// Raw tracks don't have a start time, they're delayed by repeating their first frame (rounded to whole frames, see [``TrackTrait::shift_times``]) and can't be moved earlier.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, DiscreteKeyframe, NegativeTime};
+    ///
+    /// let keyframes = |times: &[f32]| times.iter().map(|&time| DiscreteKeyframe { time, value: 1.0f32 }).collect();
+    /// let mut anim = Animation::builder()
+    ///     .global_duration(2.0)
+    ///     .add_discrete_track("n", "a", keyframes(&[0.5, 2.0]))
+    ///     .add_discrete_track("n", "b", keyframes(&[1.0]))
+    ///     .add_raw_track("n", "c", 0.25, vec![1.0f32, 2.0])
+    ///     .finish();
+    ///
+    /// anim.shift_time(1.0).unwrap();
+    /// assert_eq!(anim.global_duration, Some(3.0));
+    /// assert_eq!((anim.tracks[0].start_time(), anim.tracks[0].end_time()), (1.5, 3.0));
+    /// assert_eq!(anim.tracks[2].keyframe_count(), 6); // Four copies of the first frame in front
+    ///
+    /// assert_eq!(anim.shift_time(-1.0), Err(NegativeTime { track: 2 }));
+    /// assert_eq!(anim.tracks[0].start_time(), 1.5);
+    ///
+    /// // There's a limit to how many frames get added
+    /// anim.tracks[2].shift_times(f32::MAX);
+    /// assert_eq!(anim.tracks[2].keyframe_count(), 6 + (1 << 20));
+    /// ```
+    pub fn shift_time(&mut self, delta: f32) -> Result<(), NegativeTime> {
+        if let Some(track) = self.tracks.iter().position(|t| t.keyframe_count() > 0 && t.start_time() + delta < 0.0) {
+            return Err(NegativeTime { track });
+        }
+
+        for track in &mut self.tracks {
+            track.shift_times(delta);
+        }
+        self.global_duration = self.global_duration.map(|d| d + delta);
+        Ok(())
+    }
+
+    /// Plays ``b`` right after ``a``, the tracks of ``b`` are moved by the duration of ``a`` the way [``Animation::shift_time``] moves them
+    ///
+    /// Tracks of ``b`` animating the same property as a track of ``a`` are merged into it, the keyframe of ``a`` wins if both have one at the same time.
+    /// A raw track can't have a gap, so the frames of a raw track in ``b`` start right after the last frame of ``a``'s if that ends too late,
+    /// and ``a``'s last frame is held until they start otherwise ([``TrackTrait::append_frames``]).
+    /// Raw tracks with different intervals become a single discrete track with a keyframe for every frame.
+    /// Tracks that can't be merged (different types) are added as separate tracks, like the tracks only ``b`` has.\
+    /// The name comes from ``a`` and the duration is the sum of both durations, or longer if raw frames had to start late.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, CurveKeyframe};
+    ///
+    /// let wave = |node: &str| Animation::builder()
+    ///     .global_duration(2.0)
+    ///     .add_curve_track(node, "Rotation", vec![CurveKeyframe::linear(0.0, 0.0f32), CurveKeyframe::linear(1.0, 90.0)])
+    ///     .finish();
+    ///
+    /// let both = Animation::concat(&wave("Arm"), &wave("Arm"));
+    /// assert_eq!(both.global_duration, Some(4.0));
+    /// assert_eq!(both.tracks.len(), 1);
+    /// assert_eq!((both.tracks[0].start_time(), both.tracks[0].end_time()), (0.0, 3.0));
+    /// assert_eq!(both.tracks[0].keyframe_count(), 4);
+    ///
+    /// let other = Animation::concat(&wave("Arm"), &wave("Leg"));
+    /// assert_eq!(other.tracks[1].node(), Some("Leg"));
+    /// assert_eq!(other.tracks[1].start_time(), 2.0);
+    /// ```
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, DiscreteData, DiscreteKeyframe, RawData, Track, types::{Float, TrackType}};
+    ///
+    /// let a = Animation::builder().add_raw_track("n", "p", 0.5, vec![1.0f32, 2.0, 3.0]).finish();
+    /// let b = Animation::builder().add_raw_track("n", "p", 0.5, vec![4.0f32, 5.0]).finish();
+    /// let both = Animation::concat(&a, &b);
+    /// assert_eq!(both.tracks.len(), 1);
+    /// let track = both.tracks[0].downcast::<Track<RawData<Float>>>().unwrap();
+    /// assert_eq!(track.data.keyframes, [1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// assert_eq!(both.global_duration, Some(2.0));
+    ///
+    /// let b = Animation::builder().add_raw_track("n", "p", 0.25, vec![4.0f32, 5.0]).finish();
+    /// let both = Animation::concat(&a, &b);
+    /// assert_eq!(both.tracks[0].track_type(), TrackType::Discrete);
+    /// let track = both.tracks[0].downcast::<Track<DiscreteData<Float>>>().unwrap();
+    /// let keyframes: Vec<_> = track.data.keyframes.iter().map(|k| (k.time, k.value)).collect();
+    /// assert_eq!(keyframes, [(0.0, 1.0), (0.5, 2.0), (1.0, 3.0), (1.5, 4.0), (1.75, 5.0)]);
+    ///
+    /// // Keyframes before 0 are fine, they just stay before the end of ``a``
+    /// let early = Animation::builder().add_discrete_track("n", "p", vec![DiscreteKeyframe { time: -1.0, value: 1.0f32 }]).finish();
+    /// let both = Animation::concat(&Animation::default(), &early);
+    /// assert_eq!(both.tracks[0].start_time(), -1.0);
+    /// ```
+    pub fn concat(a: &Animation, b: &Animation) -> Animation {
+        let offset = a.duration().max(0.0);
+        let mut output = a.clone();
+
+        for track in &b.tracks {
+            let mut shifted = track.clone();
+            shifted.shift_times(offset);
+            let Some(existing) = output.tracks.iter_mut().find(|t| (t.node(), t.property()) == (track.node(), track.property())) else {
+                output.tracks.push(shifted);
+                continue;
+            };
+
+            let merged = if is_raw(existing.as_ref()) && is_raw(track.as_ref()) {
+                // Starting at the last frame of ``a`` would put both frames at the same time
+                let start = match existing.keyframe_count() {
+                    0 => offset,
+                    _ => offset.max(existing.end_time() + existing.interval().unwrap_or_default()),
+                };
+                existing.append_frames(track.as_ref(), start) || {
+                    let mut merged = frames_to_discrete(existing.as_ref());
+                    let mut appended = frames_to_discrete(track.as_ref());
+                    appended.offset_times(start);
+                    let success = merged.merge_keyframes(appended.as_ref());
+                    if success {
+                        *existing = merged;
+                    }
+                    success
+                }
+            } else {
+                existing.merge_keyframes(shifted.as_ref())
+            };
+            if !merged {
+                output.tracks.push(shifted);
+            }
+        }
+        output.global_duration = Some((offset + b.duration()).max(output.computed_duration()));
+        output
+    }
+
+    /// Stretches the animation in time by ``factor``, so 2 plays it at half speed\
+    /// Keyframe times, raw intervals and ``global_duration`` are scaled, curve tangents keep their shape
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, CurveData, Track};
+    ///
+    /// let mut anim: Animation = serde_json::from_str(r#"{"tracks": [{"trackType": "Curve", "valueType": "float", "data": {"node": "n", "property": "p", "keyframes": [
+    ///     {"time": 0.0, "value": 0.0, "interpolation": "Tangent", "leftTangent": [-1.0, 0.0], "rightTangent": [0.25, 1.0]},
+    ///     {"time": 1.0, "value": 1.0, "interpolation": "Tangent", "leftTangent": [-0.5, 1.0], "rightTangent": [1.0, 0.0]}
+    /// ]}}]}"#).unwrap();
+    /// let shape = |anim: &Animation| anim.tracks[0].as_any().downcast_ref::<Track<CurveData<f32>>>().unwrap().data.to_bezier();
+    ///
+    /// let before = shape(&anim);
+    /// anim.retime(2.0);
+    /// let after = shape(&anim);
+    /// for time in [0.1, 0.25, 0.5, 0.9] {
+    ///     assert!((before.sample(time).unwrap() - after.sample(time * 2.0).unwrap()).abs() < 1e-5);
+    /// }
+    /// ```
+    pub fn retime(&mut self, factor: f32) {
+        for track in &mut self.tracks {
+            track.scale_times(factor);
+        }
+        self.global_duration = self.global_duration.map(|d| d * factor);
+    }
+
+    /// Stretches the animation in time so it lasts ``duration`` seconds, see [``Animation::retime``]\
+    /// The current length is ``global_duration``, or [``Animation::computed_duration``] without one. Zero length animations are left alone
+    pub fn scale_time_to(&mut self, duration: f32) {
+        let current = self.duration();
+        if current > 0.0 {
+            self.retime(duration / current);
+            self.global_duration = Some(duration);
+        }
+    }
+
+    /// Multiplies the values of every numeric track by ``factor``, useful for converting units
+    ///
+    /// Tracks of bools, strings, colors and quaternions are skipped, integer values are rounded
+    pub fn scale_all_numeric_values(&mut self, factor: f32) {
+        for track in &mut self.tracks {
+            track.scale_values(factor);
+        }
+    }
+
+    /// Length of the animation according to its keyframes, which is the latest keyframe time across all tracks\
+    /// Raw tracks end at ``interval * (frames - 1)``
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let mut anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Raw", "valueType": "float", "data": {"node": "n", "property": "a", "interval": 0.5, "keyframes": [0.0, 1.0, 2.0, 3.0]}},
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "n", "property": "b", "keyframes": [
+    ///         {"time": 0.25, "value": 0.0}, {"time": 1.25, "value": 1.0}
+    ///     ]}}
+    /// ]}"#).unwrap();
+    ///
+    /// assert_eq!(anim.computed_duration(), 1.5);
+    /// assert_eq!(anim.duration(), 1.5);
+    ///
+    /// anim.tracks.remove(0);
+    /// assert_eq!(anim.computed_duration(), 1.25);
+    ///
+    /// anim.global_duration = Some(4.0);
+    /// assert_eq!(anim.duration(), 4.0);
+    /// ```
+    pub fn computed_duration(&self) -> f32 {
+        self.tracks.iter().map(|t| t.end_time()).fold(0.0, f32::max)
+    }
+
+    /// ``global_duration`` if it's set, otherwise [``Animation::computed_duration``]
+    pub fn duration(&self) -> f32 {
+        self.global_duration.unwrap_or_else(|| self.computed_duration())
+    }
+
+    /// Locations of keyframes with a NaN or infinite time, value or tangent, as ``(track index, keyframe index)`` pairs
+    pub fn find_nonfinite(&self) -> Vec<(usize, usize)> {
+        self.tracks.iter().enumerate()
+            .flat_map(|(track, t)| t.nonfinite_keyframes().into_iter().map(move |keyframe| (track, keyframe)))
+            .collect()
+    }
+
+    /// Amount of keyframes with a NaN or infinite time, value or tangent, which Resonite doesn't handle well
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let mut anim: Animation = serde_json::from_str(r#"{"tracks": [{"trackType": "Raw", "valueType": "float",
+    ///     "data": {"node": "n", "property": "p", "interval": 0.1, "keyframes": [0.0, 1.0, 2.0]}}]}"#).unwrap();
+    /// assert_eq!(anim.count_nonfinite(), 0);
+    ///
+    /// anim.scale_all_numeric_values(f32::NAN);
+    /// assert_eq!(anim.count_nonfinite(), 3);
+    /// ```
+    pub fn count_nonfinite(&self) -> usize {
+        self.tracks.iter().map(|t| t.nonfinite_keyframes().len()).sum()
+    }
+
+    /// Makes sure ``global_duration`` covers every keyframe, since Resonite cuts off anything past it
+    /// 
+    /// ``global_duration`` is set to the larger of itself and [``Animation::computed_duration``]
+    pub fn repair_duration(&mut self) {
+        let computed = self.computed_duration();
+        self.global_duration = Some(self.global_duration.map_or(computed, |d| d.max(computed)));
+    }
+
+    /// Removes every keyframe after ``max`` and shortens ``global_duration`` to at most ``max``
+    pub fn clamp_duration(&mut self, max: f32) {
+        for track in &mut self.tracks {
+            track.truncate(max);
+        }
+        self.global_duration = self.global_duration.map(|d| d.min(max));
+    }
+
+    /// Points every track animating a node in ``map`` at the node it maps to, for retargeting onto a differently named rig
+    ///
+    /// Tracks of nodes that aren't in ``map`` (and tracks without a node) stay as they are.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use resonite_core::animation::{Animation, DiscreteKeyframe};
+    ///
+    /// let keyframes = vec![DiscreteKeyframe { time: 0.0, value: 1.0f32 }];
+    /// let mut anim = Animation::builder()
+    ///     .add_discrete_track("Hips", "Rotation", keyframes.clone())
+    ///     .add_discrete_track("Spine", "Rotation", keyframes.clone())
+    ///     .add_discrete_track("Head", "Rotation", keyframes)
+    ///     .finish();
+    ///
+    /// let map = HashMap::from([("Hips".to_string(), "pelvis".to_string()), ("Spine".to_string(), "spine_01".to_string())]);
+    /// anim.rename_nodes(&map);
+    /// let nodes: Vec<_> = anim.tracks.iter().map(|t| t.node()).collect();
+    /// assert_eq!(nodes, [Some("pelvis"), Some("spine_01"), Some("Head")]);
+    ///
+    /// anim.rename_node("Head", "head");
+    /// assert_eq!(anim.tracks[2].node(), Some("head"));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn rename_nodes(&mut self, map: &HashMap<String, String>) {
+        for track in &mut self.tracks {
+            if let Some(node) = track.node().and_then(|node| map.get(node)) {
+                track.set_node(Some(node.clone()));
+            }
+        }
+    }
+
+    /// Points every track animating ``from`` at ``to`` instead, see [``Animation::rename_nodes``]
+    pub fn rename_node(&mut self, from: &str, to: &str) {
+        for track in &mut self.tracks {
+            if track.node() == Some(from) {
+                track.set_node(Some(to.to_string()));
+            }
+        }
+    }
+
+    /// Copies the tracks of every node into an animation of their own, tracks without a node end up under ``None``
+    ///
+    /// Every part keeps the name, duration and extra fields, the tracks stay in the order they were in.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, DiscreteKeyframe};
+    ///
+    /// let keyframes = vec![DiscreteKeyframe { time: 0.0, value: 1.0f32 }];
+    /// let mut anim = Animation::builder()
+    ///     .name("Walk")
+    ///     .add_discrete_track("Hips", "Position", keyframes.clone())
+    ///     .add_discrete_track("Spine", "Rotation", keyframes.clone())
+    ///     .add_discrete_track("Hips", "Rotation", keyframes.clone())
+    ///     .add_discrete_track("", "Visible", keyframes)
+    ///     .finish();
+    /// anim.tracks[3].set_node(None);
+    ///
+    /// let parts = anim.split_by_node();
+    /// assert_eq!(parts.len(), 3);
+    /// assert_eq!(parts.values().map(|part| part.tracks.len()).sum::<usize>(), anim.tracks.len());
+    ///
+    /// let hips = &parts[&Some("Hips".to_owned())];
+    /// assert_eq!(hips.name.as_deref(), Some("Walk"));
+    /// let properties: Vec<_> = hips.tracks.iter().map(|t| t.property()).collect();
+    /// assert_eq!(properties, [Some("Position"), Some("Rotation")]);
+    /// assert_eq!(parts[&None].tracks[0].property(), Some("Visible"));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn split_by_node(&self) -> HashMap<Option<String>, Animation> {
+        let mut parts: HashMap<Option<String>, Animation> = HashMap::new();
+        for track in &self.tracks {
+            parts.entry(track.node().map(str::to_owned))
+                .or_insert_with(|| Animation { name: self.name.clone(), global_duration: self.global_duration, tracks: Vec::new(), extra: self.extra.clone() })
+                .tracks.push(track.clone());
+        }
+        parts
+    }
+
+    /// Copies the track at ``index`` into an animation of its own, with the same name, duration and extra fields\
+    /// ``None`` if there's no track at ``index``
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, DiscreteKeyframe};
+    ///
+    /// let keyframes = vec![DiscreteKeyframe { time: 0.0, value: 1.0f32 }];
+    /// let anim = Animation::builder()
+    ///     .global_duration(2.0)
+    ///     .add_discrete_track("Hips", "Position", keyframes.clone())
+    ///     .add_discrete_track("Hips", "Rotation", keyframes.clone())
+    ///     .add_discrete_track("Spine", "Rotation", keyframes)
+    ///     .finish();
+    ///
+    /// let rotation = anim.extract_track(1).unwrap();
+    /// assert_eq!(rotation.global_duration, Some(2.0));
+    /// assert_eq!(rotation.tracks.len(), 1);
+    /// assert_eq!((rotation.tracks[0].node(), rotation.tracks[0].property()), (Some("Hips"), Some("Rotation")));
+    /// assert!(anim.extract_track(3).is_none());
+    ///
+    /// let spine = anim.extract_by_property(Some("Spine"), "Rotation").unwrap();
+    /// assert_eq!(spine.tracks[0].node(), Some("Spine"));
+    /// assert!(anim.extract_by_property(Some("Spine"), "Position").is_none());
+    /// ```
+    pub fn extract_track(&self, index: usize) -> Option<Animation> {
+        let track = self.tracks.get(index)?;
+        Some(Animation { name: self.name.clone(), global_duration: self.global_duration, tracks: vec![track.clone()], extra: self.extra.clone() })
+    }
+
+    /// Copies the first track animating ``property`` of ``node`` into an animation of its own, see [``Animation::extract_track``]
+    pub fn extract_by_property(&self, node: Option<&str>, property: &str) -> Option<Animation> {
+        self.extract_track(self.track_index(node, Some(property))?)
+    }
+
+    /// Removes every track without any keyframes, returns how many were removed
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let mut anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "n", "property": "a", "keyframes": []}},
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "n", "property": "b", "keyframes": [{"time": 0.0, "value": 1.0}]}},
+    ///     {"trackType": "Raw", "valueType": "float", "data": {"node": "n", "property": "c", "interval": 0.1, "keyframes": []}}
+    /// ]}"#).unwrap();
+    ///
+    /// assert_eq!(anim.remove_empty_tracks(), 2);
+    /// assert_eq!(anim.tracks.len(), 1);
+    /// assert_eq!(anim.tracks[0].property(), Some("b"));
+    /// ```
+    pub fn remove_empty_tracks(&mut self) -> usize {
+        let before = self.tracks.len();
+        self.tracks.retain(|t| t.keyframe_count() > 0);
+        before - self.tracks.len()
+    }
+
+    /// Gets rid of tracks animating the same property of the same node as an earlier track, returns how many were removed
+    ///
+    /// Which one is kept is up to ``policy``, the remaining track takes the place of the first one
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, DuplicatePolicy, types::TrackType};
+    ///
+    /// let animj = r#"{"tracks": [
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "n", "property": "p", "keyframes": [{"time": 0.0, "value": 1.0}]}},
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "n", "property": "p", "keyframes": [{"time": 0.0, "value": 2.0}, {"time": 1.0, "value": 3.0}]}}
+    /// ]}"#;
+    ///
+    /// for (policy, keyframes) in [(DuplicatePolicy::KeepFirst, 1), (DuplicatePolicy::KeepLast, 2), (DuplicatePolicy::Merge, 2)] {
+    ///     let mut anim: Animation = serde_json::from_str(animj).unwrap();
+    ///     assert_eq!(anim.deduplicate_tracks(policy), 1);
+    ///     assert_eq!(anim.tracks.len(), 1);
+    ///     assert_eq!(anim.tracks[0].keyframe_count(), keyframes);
+    /// }
+    ///
+    /// // Raw tracks are merged as discrete tracks, quaternions and vectors aren't merged at all
+    /// let mut anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Raw", "valueType": "float", "data": {"node": "n", "property": "p", "interval": 0.5, "keyframes": [1.0, 2.0]}},
+    ///     {"trackType": "Raw", "valueType": "float", "data": {"node": "n", "property": "p", "interval": 0.25, "keyframes": [3.0, 4.0, 5.0]}},
+    ///     {"trackType": "Discrete", "valueType": "floatq", "data": {"node": "n", "property": "q", "keyframes": [{"time": 0.0, "value": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0}}]}},
+    ///     {"trackType": "Discrete", "valueType": "float4", "data": {"node": "n", "property": "q", "keyframes": [{"time": 1.0, "value": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0}}]}}
+    /// ]}"#).unwrap();
+    /// assert_eq!(anim.deduplicate_tracks(DuplicatePolicy::Merge), 2);
+    /// assert_eq!(anim.tracks[0].track_type(), TrackType::Discrete);
+    /// assert_eq!(anim.tracks[0].keyframe_count(), 3); // 0 and 0.5 from the first, 0.25 from the second
+    /// assert_eq!(anim.tracks[1].keyframe_count(), 1);
+    /// ```
+    pub fn deduplicate_tracks(&mut self, policy: DuplicatePolicy) -> usize {
+        let before = self.tracks.len();
+        let mut i = 0;
+        while i < self.tracks.len() {
+            let target = (self.tracks[i].node(), self.tracks[i].property());
+            let Some(j) = (i + 1..self.tracks.len()).find(|&j| (self.tracks[j].node(), self.tracks[j].property()) == target) else {
+                i += 1;
+                continue;
+            };
+
+            match policy {
+                DuplicatePolicy::KeepFirst => {},
+                DuplicatePolicy::KeepLast => self.tracks.swap(i, j),
+                DuplicatePolicy::Merge => {
+                    let (first, rest) = self.tracks.split_at_mut(j);
+                    let (first, second) = (&mut first[i], rest[0].as_ref());
+                    if !first.merge_keyframes(second) {
+                        // Raw tracks can't have keyframes at any time, discrete ones with a keyframe for every frame can
+                        let promote = |track: &dyn TrackTrait| if is_raw(track) { frames_to_discrete(track) } else { track.clone_box() };
+                        let mut merged = promote(first.as_ref());
+                        if merged.merge_keyframes(promote(second).as_ref()) {
+                            *first = merged;
+                        }
+                    }
+                },
+            }
+            self.tracks.remove(j);
+        }
+        before - self.tracks.len()
+    }
+
+    /// Merges scalar tracks animating the components of a vector into a single track of ``value_type``
+    ///
+    /// The components are the ``Float`` (or ``Double``) tracks on ``node`` with the properties ``{base_property}.x``, ``{base_property}.y``, etc.
+    /// and are replaced by one track animating ``base_property``.\
+    /// Raw components sharing an interval stay raw, otherwise the components are sampled at every one of their keyframe times
+    /// and the result is discrete if they were all discrete (or all raw), or a curve if not.
+    ///
+    /// Only float and double vectors (including quaternions) are supported
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, DiscreteData, Track, types::{Float2, ValueType}};
+    ///
+    /// let mut anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Raw", "valueType": "float", "data": {"node": "n", "property": "p.x", "interval": 0.1, "keyframes": [0.0, 1.0, 2.0]}},
+    ///     {"trackType": "Raw", "valueType": "float", "data": {"node": "n", "property": "p.y", "interval": 0.2, "keyframes": [0.0, 1.0]}}
+    /// ]}"#).unwrap();
+    ///
+    /// anim.combine_scalar_tracks(Some("n"), "p", ValueType::Float2).unwrap();
+    /// let track = anim.tracks[0].as_any().downcast_ref::<Track<DiscreteData<Float2>>>().unwrap();
+    /// let times: Vec<f32> = track.data.keyframes.iter().map(|k| k.time).collect();
+    /// assert_eq!(times, [0.0, 0.1, 0.2]);
+    /// ```
+    pub fn combine_scalar_tracks(&mut self, node: Option<&str>, base_property: &str, value_type: ValueType) -> Result<(), CombineError> {
+        match value_type {
+            ValueType::Float2 => self.combine::<Float, Float2>(node, base_property, value_type),
+            ValueType::Float3 => self.combine::<Float, Float3>(node, base_property, value_type),
+            ValueType::Float4 => self.combine::<Float, Float4>(node, base_property, value_type),
+            ValueType::FloatQ => self.combine::<Float, FloatQ>(node, base_property, value_type),
+            ValueType::Double2 => self.combine::<Double, Double2>(node, base_property, value_type),
+            ValueType::Double3 => self.combine::<Double, Double3>(node, base_property, value_type),
+            ValueType::Double4 => self.combine::<Double, Double4>(node, base_property, value_type),
+            ValueType::DoubleQ => self.combine::<Double, DoubleQ>(node, base_property, value_type),
+            _ => Err(CombineError::UnsupportedValueType(value_type)),
+        }
+    }
+
+    /// Splits a vector track into scalar tracks for each of its components, the inverse of [``Animation::combine_scalar_tracks``]
+    ///
+    /// The track on ``node`` animating ``property`` is replaced by tracks animating ``{property}.x``, ``{property}.y``, etc.
+    /// of the same track type.\
+    /// Only float and double vectors (including quaternions) are supported
+    pub fn split_vector_track(&mut self, node: Option<&str>, property: &str) -> Result<(), CombineError> {
+        let index = self.tracks.iter()
+            .position(|t| t.node() == node && t.property() == Some(property))
+            .ok_or_else(|| CombineError::MissingTrack(property.to_owned()))?;
+
+        // Quaternions are the same type as the 4 component vectors, so they don't need their own case
+        let any = self.tracks[index].as_any();
+        let components = split::<Float, Float2>(any, ValueType::Float)
+            .or_else(|| split::<Float, Float3>(any, ValueType::Float))
+            .or_else(|| split::<Float, Float4>(any, ValueType::Float))
+            .or_else(|| split::<Double, Double2>(any, ValueType::Double))
+            .or_else(|| split::<Double, Double3>(any, ValueType::Double))
+            .or_else(|| split::<Double, Double4>(any, ValueType::Double))
+            .ok_or_else(|| CombineError::MismatchedTrack(property.to_owned()))?;
+
+        self.tracks.splice(index..=index, components);
+        Ok(())
+    }
+
+    fn combine<S, V>(&mut self, node: Option<&str>, base_property: &str, value_type: ValueType) -> Result<(), CombineError>
+        where S: ValueTrait + Lerp + Clone + Default + Serialize + 'static, V: Components<S> + ValueTrait + Serialize + 'static
+    {
+        let mut indices = Vec::with_capacity(V::COUNT);
+        let mut components = Vec::with_capacity(V::COUNT);
+        for name in &COMPONENT_NAMES[..V::COUNT] {
+            let property = format!("{base_property}.{name}");
+            let index = self.tracks.iter()
+                .position(|t| t.node() == node && t.property() == Some(property.as_str()))
+                .ok_or_else(|| CombineError::MissingTrack(property.clone()))?;
+            let component = Component::<S>::new(self.tracks[index].as_ref())
+                .ok_or(CombineError::MismatchedTrack(property))?;
+            indices.push(index);
+            components.push(component);
+        }
+
+        let combine = |time: f32| V::from_components(|i| components[i].sample(time));
+        let node = node.map(str::to_owned);
+        let property = Some(base_property.to_owned());
+
+        let raw_interval = match components[0] {
+            Component::Raw(data) => data.interval,
+            _ => None,
+        };
+        let track: Box<dyn TrackTrait> = if raw_interval.is_some() && components.iter().all(|c| matches!(c, Component::Raw(data) if data.interval == raw_interval)) {
+            let interval = raw_interval.unwrap_or_default();
+            let frames = components.iter().map(|c| c.times().len()).max().unwrap_or_default();
+            let keyframes = (0..frames).map(|i| combine(i as f32 * interval)).collect();
+            Box::new(Track { track_type: TrackType::Raw, value_type, data: RawData { node, property, interval: raw_interval, keyframes } })
+        } else {
+            let mut times: Vec<f32> = components.iter().flat_map(Component::times).collect();
+            times.sort_by(f32::total_cmp);
+            times.dedup();
+
+            // Raw tracks can't have uneven timing, so raw components with different intervals get promoted to a discrete track
+            let all_raw = components.iter().all(|c| matches!(c, Component::Raw(_)));
+            if all_raw || components.iter().all(|c| matches!(c, Component::Discrete(_))) {
+                let keyframes = times.into_iter().map(|time| DiscreteKeyframe { time, value: combine(time) }).collect();
+                Box::new(Track { track_type: TrackType::Discrete, value_type, data: DiscreteData { node, property, keyframes } })
+            } else {
+                let keyframes = times.into_iter().map(|time| CurveKeyframe {
+                    time,
+                    value: combine(time),
+                    interpolation: if components.iter().all(|c| c.holds(time)) { Interpolation::Hold } else { Interpolation::Linear },
+                    left_tangent: None,
+                    right_tangent: None,
+                }).collect();
+                Box::new(Track { track_type: TrackType::Curve, value_type, data: CurveData { node, property, keyframes } })
+            }
+        };
+
+        let first = indices.iter().copied().min().unwrap_or_default();
+        indices.sort_unstable();
+        for index in indices.into_iter().rev() {
+            self.tracks.remove(index);
+        }
+        self.tracks.insert(first, track);
+
+        Ok(())
+    }
+}
+
+metamatch::quote! {
+    [<for (data, is_raw, example) in [(RawData, true, false), (DiscreteData, false, true), (CurveData, false, false), (BezierData, false, false)]>]
+        #[allow(private_bounds)]
+        impl<T> [<ident(str(data))>]<T> where T: ValueTrait {
+            /// Removes the keyframe at ``index``, ``false`` if there isn't one
+            [<if is_raw>]
+            ///
+            /// Raw frames are only timed by their position, so every frame after the removed one moves ``interval`` earlier
+            ///
+            /// ```
+            /// use resonite_core::animation::RawData;
+            ///
+            /// let mut data = RawData { node: None, property: None, interval: Some(0.5), keyframes: vec![1.0f32, 2.0, 3.0] };
+            /// assert!(data.remove_keyframe_at_index(1));
+            /// assert_eq!(data.keyframes, [1.0, 3.0]);
+            /// assert_eq!(data.sample(0.5), Some(3.0)); // Used to be at 1.0
+            /// assert!(!data.remove_keyframe_at_index(2));
+            /// ```
+            [</if>]
+            pub fn remove_keyframe_at_index(&mut self, index: usize) -> bool {
+                let removed = index < self.keyframes.len();
+                if removed {
+                    self.keyframes.remove(index);
+                }
+                removed
+            }
+
+            /// Removes the keyframe within ``epsilon`` of ``time``, ``false`` if there isn't one\
+            /// If several are that close, only the last one is removed
+            [<if is_raw>]
+            ///
+            /// A frame's time is ``interval * index``, and like with [``RawData::remove_keyframe_at_index``] the frames after it move ``interval`` earlier
+            ///
+            /// ```
+            /// use resonite_core::animation::RawData;
+            ///
+            /// let mut data = RawData { node: None, property: None, interval: Some(0.5), keyframes: vec![1.0f32, 2.0, 3.0] };
+            /// assert!(!data.remove_keyframe_at_time(0.75, 0.1));
+            /// assert!(data.remove_keyframe_at_time(0.45, 0.1));
+            /// assert_eq!(data.keyframes, [1.0, 3.0]);
+            /// ```
+            [</if>]
+            [<if example>]
+            ///
+            /// ```
+            /// use resonite_core::animation::{DiscreteData, DiscreteKeyframe};
+            ///
+            /// let keyframes = [0.0, 1.0, 2.0].map(|time| DiscreteKeyframe { time, value: time as i32 }).to_vec();
+            /// let mut data = DiscreteData { node: None, property: None, keyframes };
+            /// assert!(!data.remove_keyframe_at_time(1.5, 0.1));
+            /// assert!(data.remove_keyframe_at_time(1.05, 0.1));
+            /// assert_eq!(data.keyframes.iter().map(|k| k.time).collect::<Vec<_>>(), [0.0, 2.0]);
+            /// assert!(!data.remove_keyframe_at_index(2));
+            /// ```
+            [</if>]
+            pub fn remove_keyframe_at_time(&mut self, time: f32, epsilon: f32) -> bool {
+                keyframe_near(self, time, epsilon).is_some_and(|index| self.remove_keyframe_at_index(index))
+            }
+        }
+    [</for>]
+}
+
+/// Index of the last keyframe within ``epsilon`` of ``time``
+fn keyframe_near(data: &impl KeyframeTrait, time: f32, epsilon: f32) -> Option<usize> {
+    let index = data.keyframe_index_at(time + epsilon)?;
+    data.keyframe_time(index).filter(|&t| t >= time - epsilon).map(|_| index)
+}
+
+/// Whether the track is a raw track with an interval, which can't have keyframes at any time
+fn is_raw(track: &dyn TrackTrait) -> bool {
+    track.track_type() == TrackType::Raw && track.interval().is_some()
+}
+
+/// A discrete track with a keyframe for every frame of a raw track
+fn frames_to_discrete(track: &dyn TrackTrait) -> Box<dyn TrackTrait> {
+    let times: Vec<f32> = (0..track.keyframe_count()).filter_map(|i| track.keyframe_time(i)).collect();
+    track.sample_to_discrete(&times)
+}
+
+/// Error for [``Animation::combine_scalar_tracks``] and [``Animation::split_vector_track``]
+#[derive(Debug)]
+pub enum CombineError {
+    /// Only float and double vectors can be combined or split
+    UnsupportedValueType(ValueType),
+    /// There's no track animating this property on the node
+    MissingTrack(String),
+    /// The track animating this property doesn't have the expected value type
+    MismatchedTrack(String),
+}
+
+/// Error for [``Animation::shift_time``], a keyframe of the track at this index would end up before 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegativeTime {
+    pub track: usize,
+}
+
+/// Which track [``Animation::deduplicate_tracks``] keeps when several animate the same property
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Keep the first track
+    KeepFirst,
+    /// Keep the last track
+    KeepLast,
+    /// Merge the keyframes into the first track, the first track wins where both have a keyframe at the same time\
+    /// Raw tracks become discrete tracks with a keyframe for every frame to be merged, tracks that still can't be merged (different types) behave like ``KeepFirst``
+    Merge,
+}
+
+const COMPONENT_NAMES: [&str; 4] = ["x", "y", "z", "w"];
+
+/// Vectors that can be built from (and taken apart into) scalar components
+trait Components<S> {
+    const COUNT: usize;
+    fn from_components(component: impl Fn(usize) -> S) -> Self;
+    fn component(&self, index: usize) -> S;
+}
+
+metamatch::quote! {
+    [<for (scalar, count) in [(Float, 2), (Float, 3), (Float, 4), (Double, 2), (Double, 3), (Double, 4)]>]
+        impl Components<[<ident(str(scalar))>]> for [<ident(str(scalar) + str(count))>] {
+            const COUNT: usize = [<count>];
+
+            fn from_components(component: impl Fn(usize) -> [<ident(str(scalar))>]) -> Self {
+                Self {
+                    [<for index in 0..count>]
+                        [<let field = [x,y,z,w][index]>]
+                        [<ident(str(field))>]: component([<index>]),
+                    [</for>]
+                }
+            }
+
+            fn component(&self, index: usize) -> [<ident(str(scalar))>] {
+                [
+                    [<for index in 0..count>]
+                        [<let field = [x,y,z,w][index]>]
+                        self.[<ident(str(field))>],
+                    [</for>]
+                ][index]
+            }
+        }
+    [</for>]
+}
+
+/// A scalar track being combined
+enum Component<'a, S> where S: ValueTrait {
+    Raw(&'a RawData<S>),
+    Discrete(&'a DiscreteData<S>),
+    Curve(&'a CurveData<S>),
+}
+
+impl<'a, S> Component<'a, S> where S: ValueTrait + Lerp + Clone + Default + Serialize + 'static {
+    fn new(track: &'a dyn TrackTrait) -> Option<Self> {
+        let any = track.as_any();
+        if let Some(track) = any.downcast_ref::<Track<RawData<S>>>() {
+            Some(Self::Raw(&track.data))
+        } else if let Some(track) = any.downcast_ref::<Track<DiscreteData<S>>>() {
+            Some(Self::Discrete(&track.data))
+        } else {
+            any.downcast_ref::<Track<CurveData<S>>>().map(|track| Self::Curve(&track.data))
+        }
+    }
+
+    fn times(&self) -> Vec<f32> {
+        match self {
+            Self::Raw(data) => {
+                let interval = data.interval.unwrap_or_default();
+                (0..data.keyframes.len()).map(|i| i as f32 * interval).collect()
+            },
+            Self::Discrete(data) => data.keyframes.iter().map(|k| k.time).collect(),
+            Self::Curve(data) => data.keyframes.iter().map(|k| k.time).collect(),
+        }
+    }
+
+    fn sample(&self, time: f32) -> S {
+        match self {
+            Self::Raw(data) => data.sample(time),
+            Self::Discrete(data) => data.sample(time),
+            Self::Curve(data) => data.sample(time),
+        }.unwrap_or_default()
+    }
+
+    /// Whether the value stays the same from ``time`` until the next keyframe
+    fn holds(&self, time: f32) -> bool {
+        match self {
+            Self::Raw(_) => false,
+            Self::Discrete(_) => true,
+            Self::Curve(data) => {
+                let index = data.keyframes.partition_point(|k| k.time <= time).saturating_sub(1);
+                data.keyframes.get(index).is_none_or(|k| k.interpolation == Interpolation::Hold)
+            },
+        }
+    }
+}
+
+/// Splits a vector track into one track per component
+fn split<S, V>(any: &dyn core::any::Any, scalar_type: ValueType) -> Option<Vec<Box<dyn TrackTrait>>>
+    where S: ValueTrait + Serialize + 'static, V: Components<S> + ValueTrait + Serialize + 'static
+{
+    let property = |track_property: &Option<String>, index: usize| {
+        Some(format!("{}.{}", track_property.as_deref().unwrap_or_default(), COMPONENT_NAMES[index]))
+    };
+
+    let mut output: Vec<Box<dyn TrackTrait>> = Vec::with_capacity(V::COUNT);
+    if let Some(track) = any.downcast_ref::<Track<RawData<V>>>() {
+        for i in 0..V::COUNT {
+            output.push(Box::new(Track {
+                track_type: track.track_type,
+                value_type: scalar_type,
+                data: RawData {
+                    node: track.data.node.clone(),
+                    property: property(&track.data.property, i),
+                    interval: track.data.interval,
+                    keyframes: track.data.keyframes.iter().map(|v| v.component(i)).collect(),
+                },
+            }));
+        }
+    } else if let Some(track) = any.downcast_ref::<Track<DiscreteData<V>>>() {
+        for i in 0..V::COUNT {
+            output.push(Box::new(Track {
+                track_type: track.track_type,
+                value_type: scalar_type,
+                data: DiscreteData {
+                    node: track.data.node.clone(),
+                    property: property(&track.data.property, i),
+                    keyframes: track.data.keyframes.iter().map(|k| DiscreteKeyframe { time: k.time, value: k.value.component(i) }).collect(),
+                },
+            }));
+        }
+    } else {
+        let track = any.downcast_ref::<Track<CurveData<V>>>()?;
+        for i in 0..V::COUNT {
+            output.push(Box::new(Track {
+                track_type: track.track_type,
+                value_type: scalar_type,
+                data: CurveData {
+                    node: track.data.node.clone(),
+                    property: property(&track.data.property, i),
+                    keyframes: track.data.keyframes.iter().map(|k| CurveKeyframe {
+                        time: k.time,
+                        value: k.value.component(i),
+                        interpolation: k.interpolation,
+                        left_tangent: k.left_tangent.as_ref().map(|(time, t)| (*time, t.component(i))),
+                        right_tangent: k.right_tangent.as_ref().map(|(time, t)| (*time, t.component(i))),
+                    }).collect(),
+                },
+            }));
+        }
+    }
+
+    Some(output)
+}
@@ -0,0 +1,66 @@
+//! Fixed synthetic animations for benchmarks and tests, unlike [``Animation::random``] they're the same every time
+//!
+//! Only available with the ``testing`` feature
+
+use super::{Animation, CurveKeyframe, DiscreteKeyframe, types::{Float3, Float4x4}};
+
+/// A single float curve of ``keyframes`` linear keyframes, 30 per second, following a sine wave
+///
+/// ```
+/// use resonite_core::animation::{Animation, fixtures};
+///
+/// let anim = fixtures::long_curve(1000);
+/// assert_eq!(anim.tracks[0].keyframe_count(), 1000);
+/// assert!(Animation::from_animx_slice(&anim.to_animx_bytes().unwrap()).unwrap().bit_identical(&anim));
+/// ```
+pub fn long_curve(keyframes: usize) -> Animation {
+    let keyframes = (0..keyframes).map(|i| {
+        let time = i as f32 / 30.0;
+        CurveKeyframe::linear(time, time.sin())
+    }).collect();
+    Animation::builder().name("LongCurve").add_curve_track("Node", "Value", keyframes).finish()
+}
+
+/// ``tracks`` discrete position tracks on separate nodes, each with ``keyframes`` keyframes, 30 per second
+///
+/// ```
+/// use resonite_core::animation::fixtures;
+///
+/// let anim = fixtures::many_tracks(500, 4);
+/// assert_eq!(anim.tracks.len(), 500);
+/// assert_eq!(anim.tracks[499].node(), Some("Node499"));
+/// ```
+pub fn many_tracks(tracks: usize, keyframes: usize) -> Animation {
+    let mut builder = Animation::builder().name("ManyTracks");
+    for track in 0..tracks {
+        let keyframes = (0..keyframes).map(|i| {
+            let time = i as f32 / 30.0;
+            DiscreteKeyframe { time, value: Float3::new(track as f32, time, 0.0) }
+        }).collect();
+        builder = builder.add_discrete_track(format!("Node{track}"), "Position", keyframes);
+    }
+    builder.finish()
+}
+
+/// A single raw track of ``frames`` transform matrices at 60 frames per second, spinning around Y
+///
+/// ```
+/// use resonite_core::animation::{fixtures, types::ValueType};
+///
+/// let anim = fixtures::matrix_track(120);
+/// assert_eq!(anim.tracks[0].value_type(), ValueType::Float4x4);
+/// assert_eq!(anim.tracks[0].interval(), Some(1.0 / 60.0));
+/// ```
+pub fn matrix_track(frames: usize) -> Animation {
+    let frames = (0..frames).map(|i| {
+        let (sin, cos) = (i as f32 / 60.0).sin_cos();
+        let matrix: Float4x4 = [
+            [cos, 0.0, sin, 0.0],
+            [0.0, 1.0, 0.0, i as f32 / 60.0],
+            [-sin, 0.0, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        matrix
+    }).collect();
+    Animation::builder().name("MatrixTrack").add_raw_track("Node", "Transform", 1.0 / 60.0, frames).finish()
+}
@@ -0,0 +1,73 @@
+//! Conversions to and from [``glam``] types
+//!
+//! Only available with the ``glam`` feature.\
+//! Vectors and quaternions convert with ``From``/``Into``, matrices are plain arrays so they have [``to_mat4``] & [``from_mat4``] instead.
+
+use super::types::{Float2, Float3, Float4, Float4x4, FloatQ};
+
+metamatch::quote! {
+    [<for (name, glam_name, fields) in [(Float2, Vec2, [x, y]), (Float3, Vec3, [x, y, z]), (Float4, Vec4, [x, y, z, w])]>]
+        impl From<[<ident(str(name))>]> for glam::[<ident(str(glam_name))>] {
+            fn from(value: [<ident(str(name))>]) -> Self {
+                Self::new([<for field in fields>] value.[<ident(str(field))>], [</for>])
+            }
+        }
+
+        impl From<glam::[<ident(str(glam_name))>]> for [<ident(str(name))>] {
+            fn from(value: glam::[<ident(str(glam_name))>]) -> Self {
+                Self::new([<for field in fields>] value.[<ident(str(field))>], [</for>])
+            }
+        }
+    [</for>]
+}
+
+/// Quaternions are stored as ``x, y, z, w`` like [``glam::Quat::from_xyzw``], ``w`` being the real part
+///
+/// ```
+/// use resonite_core::animation::types::FloatQ;
+///
+/// let half = 45f32.to_radians();
+/// let q = FloatQ::new(0.0, half.sin(), 0.0, half.cos());
+/// let rotation = glam::Quat::from(q);
+/// assert!(rotation.abs_diff_eq(glam::Quat::from_rotation_y(90f32.to_radians()), 1e-6));
+/// assert_eq!(FloatQ::from(rotation).w, q.w);
+/// ```
+impl From<FloatQ> for glam::Quat {
+    fn from(value: FloatQ) -> Self {
+        Self::from_xyzw(value.x, value.y, value.z, value.w)
+    }
+}
+
+impl From<glam::Quat> for FloatQ {
+    fn from(value: glam::Quat) -> Self {
+        Self::new(value.x, value.y, value.z, value.w)
+    }
+}
+
+/// Converts a matrix to glam's, transposing it
+///
+/// Matrices are stored row by row (``m[row][column]``) while [``glam::Mat4``] stores columns,
+/// so a translation is the last column of ``m`` and ends up in ``w_axis``.
+///
+/// ```
+/// use resonite_core::animation::{glam::{from_mat4, to_mat4}, types::Float4x4};
+///
+/// let m: Float4x4 = [
+///     [1.0, 0.0, 0.0, 1.0],
+///     [0.0, 1.0, 0.0, 2.0],
+///     [0.0, 0.0, 1.0, 3.0],
+///     [0.0, 0.0, 0.0, 1.0],
+/// ];
+/// let mat = to_mat4(&m);
+/// assert_eq!(mat.w_axis, glam::Vec4::new(1.0, 2.0, 3.0, 1.0));
+/// assert_eq!(mat.transform_point3(glam::Vec3::ZERO), glam::Vec3::new(1.0, 2.0, 3.0));
+/// assert_eq!(from_mat4(mat), m);
+/// ```
+pub fn to_mat4(m: &Float4x4) -> glam::Mat4 {
+    glam::Mat4::from_cols_array_2d(m).transpose()
+}
+
+/// Converts glam's matrix back, see [``to_mat4``] for the layout
+pub fn from_mat4(mat: glam::Mat4) -> Float4x4 {
+    mat.transpose().to_cols_array_2d()
+}
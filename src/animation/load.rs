@@ -0,0 +1,97 @@
+//! Loading animations without knowing which format they're in
+
+use super::{AnimJError, AnimXError, Animation};
+
+/// The two formats an animation can be stored in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimFormat {
+    /// Binary, starts with the length prefixed string ``AnimX``
+    AnimX,
+    /// JSON, an object with a ``tracks`` array
+    AnimJ,
+}
+
+/// Figures out whether ``bytes`` are AnimX or AnimJ, ``None`` if they're neither
+///
+/// AnimX is recognized by its header alone, AnimJ has to parse as a JSON object with a ``tracks`` array.
+///
+/// ```
+/// use resonite_core::animation::{AnimFormat, Animation, detect_format};
+///
+/// let animj = r#"{"name": "Empty", "tracks": []}"#;
+/// let animx = Animation::from_animj_str(animj).unwrap().to_animx_bytes().unwrap();
+///
+/// assert_eq!(detect_format(&animx), Some(AnimFormat::AnimX));
+/// assert_eq!(detect_format(animj.as_bytes()), Some(AnimFormat::AnimJ));
+/// assert_eq!(detect_format(br#"{"name": "No tracks"}"#), None);
+/// assert_eq!(detect_format(b"AnimX"), None);
+/// ```
+pub fn detect_format(bytes: &[u8]) -> Option<AnimFormat> {
+    if bytes.starts_with(b"\x05AnimX") {
+        return Some(AnimFormat::AnimX);
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(bytes).ok()?;
+    json.get("tracks").is_some_and(serde_json::Value::is_array).then_some(AnimFormat::AnimJ)
+}
+
+impl Animation {
+    /// Reads an animation from either format, see [``detect_format``]
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, LoadError};
+    ///
+    /// let animj = r#"{"name": "Empty", "tracks": []}"#;
+    /// let animx = Animation::load(animj.as_bytes()).unwrap().to_animx_bytes().unwrap();
+    /// assert_eq!(Animation::load(&animx).unwrap().name.as_deref(), Some("Empty"));
+    /// assert!(matches!(Animation::load(b"not an animation"), Err(LoadError::UnknownFormat)));
+    /// ```
+    pub fn load(bytes: &[u8]) -> Result<Animation, LoadError> {
+        match detect_format(bytes) {
+            Some(AnimFormat::AnimX) => Ok(Animation::from_animx_slice(bytes)?),
+            Some(AnimFormat::AnimJ) => Ok(serde_json::from_slice::<Animation>(bytes).map_err(AnimJError)?),
+            None => Err(LoadError::UnknownFormat),
+        }
+    }
+}
+
+/// Error from [``Animation::load``]
+#[derive(Debug)]
+pub enum LoadError {
+    /// The bytes are neither AnimX nor AnimJ
+    UnknownFormat,
+    AnimX(AnimXError),
+    AnimJ(AnimJError),
+}
+
+impl core::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::UnknownFormat => f.write_str("neither AnimX nor AnimJ"),
+            Self::AnimX(e) => e.fmt(f),
+            Self::AnimJ(e) => e.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::UnknownFormat => None,
+            Self::AnimX(e) => Some(e),
+            Self::AnimJ(e) => Some(e),
+        }
+    }
+}
+
+impl From<AnimXError> for LoadError {
+    fn from(e: AnimXError) -> Self {
+        Self::AnimX(e)
+    }
+}
+
+impl From<AnimJError> for LoadError {
+    fn from(e: AnimJError) -> Self {
+        Self::AnimJ(e)
+    }
+}
@@ -1,290 +1,1273 @@
 //! # Animation data (AnimJ & AnimX)
 
 pub mod types;
+mod edit;
+mod sample;
+mod convert;
+mod validate;
+mod decimate;
+mod trim;
+mod compare;
+mod value;
+mod builder;
+mod load;
+#[cfg(feature = "testing")]
+pub mod random;
+#[cfg(feature = "testing")]
+pub mod fixtures;
+#[cfg(feature = "csv")]
+mod csv;
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "glam")]
+pub mod glam;
+#[cfg(feature = "tokio")]
+mod async_io;
 use types::*;
+pub use edit::{CombineError, DuplicatePolicy, NegativeTime};
+pub use validate::ValidationError;
+pub use sample::{Pose, normalize, slerp};
+pub use value::{TypeMismatch, Value};
+pub use builder::AnimationBuilder;
+pub use load::{AnimFormat, LoadError, detect_format};
 
-use std::{fmt::Debug, io::{BufWriter, Read, Write}};
-use serde::{de::{Error, IgnoredAny, Visitor}, Deserialize, Deserializer};
+use alloc::{borrow::ToOwned, boxed::Box, format, string::{FromUtf8Error, String}, vec, vec::Vec};
+use core::fmt::Debug;
+#[cfg(feature = "std")]
+use std::{collections::HashMap, io::{BufReader, BufWriter, Cursor, Seek, SeekFrom}};
+use serde::{de::{Error, Visitor}, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(not(feature = "std"))]
+use crate::float::FloatExt;
+use crate::io::{self, Read, Write};
 
 /// The overarching type for animations
 /// 
-/// This type implements ``serde::Deserialize`` and is meant to be deserealized from an AnimJ (JSON) structure\
+/// This type implements ``serde::Deserialize`` and ``serde::Serialize`` and is meant to be (de)serialized as an AnimJ (JSON) structure\
 /// There are also functions for writing and reading AnimX streams (Binary)
+///
+/// A ``None`` name or duration is left out of AnimJ entirely:
+///
+/// ```
+/// use resonite_core::animation::Animation;
+///
+/// let anim = Animation { name: Some("Wave".to_owned()), ..Default::default() };
+/// assert_eq!(serde_json::to_string(&anim).unwrap(), r#"{"name":"Wave","tracks":[]}"#);
+/// ```
+///
+/// Serializing gives the same shape the deserializer takes, so AnimX can be turned into AnimJ and back without losing anything:
+///
+/// ```
+/// use resonite_core::animation::Animation;
+///
+/// let anim: Animation = serde_json::from_str(r#"{"name": "Door", "globalDuration": 1.0, "tracks": [
+///     {"trackType": "Raw", "valueType": "float3", "data": {"node": "n", "property": "p", "interval": 0.5,
+///         "keyframes": [{"x": 0.0, "y": 1.0, "z": 2.0}, {"x": 3.0, "y": 4.0, "z": 5.0}]}},
+///     {"trackType": "Curve", "valueType": "double", "data": {"node": "n", "property": "q", "keyframes": [
+///         {"time": 0.0, "value": 0.0, "interpolation": "Tangent", "leftTangent": [0.0, 0.0], "rightTangent": [0.5, 1.0]}
+///     ]}}
+/// ]}"#).unwrap();
+///
+/// let json = serde_json::to_string(&anim).unwrap();
+/// assert!(json.contains(r#""valueType":"float3""#));
+/// let back: Animation = serde_json::from_str(&json).unwrap();
+/// assert!(back.bit_identical(&anim));
+/// ```
+///
+/// Animations are ``Send + Sync``, so they can be moved to (or shared between) threads:
+///
+/// ```
+/// use resonite_core::animation::Animation;
+///
+/// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+///     {"trackType": "Curve", "valueType": "float", "data": {"node": "n", "property": "p", "keyframes": [
+///         {"time": 0.0, "value": 0.0, "interpolation": "Linear"},
+///         {"time": 1.0, "value": 2.0, "interpolation": "Linear"}
+///     ]}}
+/// ]}"#).unwrap();
+///
+/// let sample = std::thread::spawn(move || *anim.tracks[0].sample_any(0.5).unwrap().downcast::<f32>().unwrap());
+/// assert_eq!(sample.join().unwrap(), 1.0);
+/// ```
 #[allow(private_interfaces)]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Animation {
     pub name: Option<String>,
     pub global_duration: Option<f32>,
     pub tracks: Vec<Box<dyn TrackTrait>>,
+    /// Fields of the AnimJ object this crate doesn't know about, they're written back out as they were\
+    /// AnimX has nowhere to put them, so they're lost there
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim = Animation::from_animj_str(r#"{"name": "Wave", "author": "Vlams", "tracks": []}"#).unwrap();
+    /// assert_eq!(anim.extra["author"], "Vlams");
+    ///
+    /// let json = serde_json::to_string(&anim).unwrap();
+    /// assert_eq!(json, r#"{"name":"Wave","tracks":[],"author":"Vlams"}"#);
+    /// assert_eq!(Animation::from_animj_str(&json).unwrap().extra, anim.extra);
+    /// ```
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Animation>();
+};
+
 impl Animation {
-    /// Function for writing data as an AnimX stream\
-    /// Compression is not yet supported.
-    /// 
+    /// Read only access to the tracks
+    pub fn tracks(&self) -> &[Box<dyn TrackTrait>] {
+        &self.tracks
+    }
+
+    /// Index of the first track animating ``property`` of ``node``, meant to be used with ``tracks.remove``/``tracks.insert``
+    ///
     /// ```
     /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "Arm", "property": "Length", "keyframes": []}},
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "Leg", "property": "Length", "keyframes": []}},
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "Leg", "property": "Width", "keyframes": []}}
+    /// ]}"#).unwrap();
+    ///
+    /// assert_eq!(anim.track_index(Some("Leg"), Some("Width")), Some(2));
+    /// assert_eq!(anim.track_index(Some("Arm"), Some("Width")), None);
+    /// assert_eq!(anim.track(1).and_then(|t| t.node()), Some("Leg"));
+    /// ```
+    pub fn track_index(&self, node: Option<&str>, property: Option<&str>) -> Option<usize> {
+        self.tracks.iter().position(|t| t.node() == node && t.property() == property)
+    }
+
+    /// The track at ``index``, if there is one
+    pub fn track(&self, index: usize) -> Option<&dyn TrackTrait> {
+        self.tracks.get(index).map(|t| t.as_ref())
+    }
+
+    /// The first track animating ``property`` of ``node``, a linear search so use [``Animation::build_index``] for lots of lookups
+    ///
+    /// Resonite doesn't stop two tracks from animating the same property, when that happens the first one is returned
+    /// like the index does. The others can still be found through ``tracks``.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, types::ValueType};
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "Arm", "property": "Length", "keyframes": []}},
+    ///     {"trackType": "Discrete", "valueType": "int", "data": {"node": "Arm", "property": "Length", "keyframes": []}},
+    ///     {"trackType": "Discrete", "valueType": "bool", "data": {"node": null, "property": "Visible", "keyframes": []}}
+    /// ]}"#).unwrap();
+    ///
+    /// assert_eq!(anim.track_for(Some("Arm"), "Length").unwrap().value_type(), ValueType::Float);
+    /// assert!(anim.track_for(Some("Arm"), "Width").is_none());
+    /// assert!(anim.track_for(None, "Visible").is_some());
+    ///
+    /// let index = anim.build_index();
+    /// assert_eq!(index.len(), 2);
+    /// assert_eq!(index[&(Some("Arm".to_owned()), "Length".to_owned())], 0);
+    /// assert_eq!(index.get(&(None, "Visible".to_owned())), Some(&2));
+    /// ```
+    pub fn track_for(&self, node: Option<&str>, property: &str) -> Option<&dyn TrackTrait> {
+        self.track(self.track_index(node, Some(property))?)
+    }
+
+    /// Maps the ``(node, property)`` of every track to its index, for repeated lookups without [``Animation::track_for``]'s linear search\
+    /// Tracks without a property are left out, and when several tracks share a key the first one wins
+    #[cfg(feature = "std")]
+    pub fn build_index(&self) -> HashMap<(Option<String>, String), usize> {
+        let mut index = HashMap::with_capacity(self.tracks.len());
+        for (i, track) in self.tracks.iter().enumerate() {
+            let Some(property) = track.property() else { continue };
+            index.entry((track.node().map(str::to_owned), property.to_owned())).or_insert(i);
+        }
+        index
+    }
+
+    /// An empty animation with room for ``tracks`` tracks
+    pub fn with_capacity(tracks: usize) -> Animation {
+        Animation {
+            tracks: Vec::with_capacity(tracks),
+            ..Default::default()
+        }
+    }
+
+    /// Function for writing data as an AnimX stream, returns the amount of bytes written\
+    /// Errors from ``buf`` are passed on, anything already written stays written.\
+    /// The stream is uncompressed, see [``WriteOptions::encoding``] for compression.
     /// 
-    /// let anim: Animation = serde_json::from_str(/* AnimJ */)?;
-    /// let mut buf = Vec::new();
-    /// anim.write_animx(&mut buf);
+    /// ```no_run
+    /// use resonite_core::animation::Animation;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let anim: Animation = serde_json::from_str(&std::fs::read_to_string("anim.animj")?)?;
+    /// let file = std::fs::File::create("anim.animx")?;
+    /// let written = anim.write_animx(file)?;
+    /// # Ok(())
+    /// # }
     /// ```
     /// 
-    pub fn write_animx(&self, buf: impl Write) {
-        let mut writer = BufWriter::new(buf);
-        let mut write = |bytes: &[u8]| { writer.write(bytes).unwrap(); };
+    /// ```
+    /// use std::io::{self, Write};
+    /// use resonite_core::animation::{AnimXError, Animation};
+    ///
+    /// struct Full;
+    /// impl Write for Full {
+    ///     fn write(&mut self, _: &[u8]) -> io::Result<usize> { Err(io::ErrorKind::StorageFull.into()) }
+    ///     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// }
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"name": "anim", "tracks": []}"#).unwrap();
+    /// assert!(matches!(anim.write_animx(Full), Err(AnimXError::IoError(_))));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_animx(&self, buf: impl Write) -> Result<u64, AnimXError> {
+        self.write_animx_with_options(buf, &WriteOptions::default())
+    }
+
+    /// Same as [``Animation::write_animx``], but with [``WriteOptions``] to control details of the encoding
+    #[cfg(feature = "std")]
+    pub fn write_animx_with_options(&self, buf: impl Write, options: &WriteOptions) -> Result<u64, AnimXError> {
+        let mut buf = BufWriter::new(buf);
+        let written = self.write_animx_into(&mut buf, options)?;
+        buf.flush()?;
+        Ok(written)
+    }
+
+    /// Writes the whole stream into ``buf`` as is, it's up to the caller to buffer it
+    fn write_animx_into(&self, buf: &mut dyn Write, options: &WriteOptions) -> Result<u64, AnimXError> {
+        let mut writer = AnimXWriter { inner: buf, options, written: 0 };
+
+        self.write_header(&mut writer)?;
+        match options.encoding {
+            AnimXEncoding::Plain => self.write_tracks(&mut writer)?,
+            #[cfg(feature = "compression")]
+            encoding => {
+                let mut tracks = Vec::new();
+                self.write_tracks(&mut AnimXWriter { inner: &mut tracks, options, written: 0 })?;
+                writer.write(&compression::compress(encoding, &tracks)?)?;
+            },
+            #[cfg(not(feature = "compression"))]
+            _ => Err(AnimXError::UnsupportedEncoding)?,
+        }
+        Ok(writer.written)
+    }
+
+    /// Writes the animation as an AnimX stream into a new ``Vec``, see [``Animation::write_animx``]\
+    /// The easiest way to get the bytes, [``Animation::write_animx``] is still better for big animations going straight to a file
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"name": "Bytes", "tracks": [{"trackType": "Raw", "valueType": "float",
+    ///     "data": {"node": "n", "property": "p", "interval": 0.5, "keyframes": [1.0, 2.0, 3.0]}}]}"#).unwrap();
+    ///
+    /// let mut streamed = Cursor::new(Vec::new());
+    /// let written = anim.write_animx(&mut streamed).unwrap();
+    /// let bytes = anim.to_animx_bytes().unwrap();
+    /// assert_eq!(bytes, streamed.into_inner());
+    /// assert_eq!(bytes.len() as u64, written);
+    /// ```
+    pub fn to_animx_bytes(&self) -> Result<Vec<u8>, AnimXError> {
+        self.to_animx_bytes_with_options(&WriteOptions::default())
+    }
+
+    /// Same as [``Animation::to_animx_bytes``], but with [``WriteOptions``] to control details of the encoding
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, OptStringEncoding, WriteOptions};
+    ///
+    /// let anim = Animation::builder().add_raw_track("n", "p", 0.1, vec![1.0f32, 2.0]).finish();
+    /// let options = WriteOptions { target_encoding: OptStringEncoding::Flagged, ..Default::default() };
+    /// let mut streamed = Vec::new();
+    /// anim.write_animx_with_options(&mut streamed, &options).unwrap();
+    /// assert_eq!(anim.to_animx_bytes_with_options(&options).unwrap(), streamed);
+    /// ```
+    pub fn to_animx_bytes_with_options(&self, options: &WriteOptions) -> Result<Vec<u8>, AnimXError> {
+        let mut buf = Vec::new();
+        self.write_animx_into(&mut buf, options)?;
+        Ok(buf)
+    }
+
+    /// Size of the animation written as an AnimX stream, without actually keeping the bytes around
+    pub fn estimate_animx_size(&self) -> u64 {
+        self.write_animx_into(&mut io::sink(), &WriteOptions::default()).expect("writing to a sink can't fail")
+    }
+
+    /// Size of every track when written as an AnimX stream, as ``(node, property, bytes)``, useful for finding what makes a file large
+    ///
+    /// Together with the header these add up to [``Animation::estimate_animx_size``]
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"name": "anim", "tracks": [
+    ///     {"trackType": "Raw", "valueType": "double4x4", "data": {"node": "n", "property": "a", "interval": 0.1, "keyframes": []}},
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "n", "property": "b", "keyframes": [{"time": 0.0, "value": 1.0}]}}
+    /// ]}"#).unwrap();
+    ///
+    /// let sizes = anim.total_bytes_by_track();
+    /// assert_eq!(sizes[1], (Some("n".to_owned()), Some("b".to_owned()), 1 + 1 + 2 + 2 + 1 + 8));
+    /// let header = 6 + 4 + 1 + 4 + 5 + 1;
+    /// assert_eq!(sizes.iter().map(|(_, _, size)| size).sum::<usize>() + header, anim.estimate_animx_size() as usize);
+    /// ```
+    pub fn total_bytes_by_track(&self) -> Vec<(Option<String>, Option<String>, usize)> {
+        let options = WriteOptions::default();
+        self.tracks.iter().map(|track| {
+            let mut sink = io::sink();
+            let mut writer = AnimXWriter { inner: &mut sink, options: &options, written: 0 };
+            track.write(&mut writer).expect("writing to a sink can't fail");
+            (track.node().map(str::to_owned), track.property().map(str::to_owned), writer.written as usize)
+        }).collect()
+    }
+
+    /// Whether both animations end up as the exact same bytes when written as an AnimX stream
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let animj = |value: f32| format!(r#"{{"tracks": [{{"trackType": "Discrete", "valueType": "float",
+    ///     "data": {{"node": "n", "property": "p", "keyframes": [{{"time": 0.0, "value": {value:?}}}]}}}}]}}"#);
+    /// let a: Animation = serde_json::from_str(&animj(1.0)).unwrap();
+    /// let b: Animation = serde_json::from_str(&animj(1.0)).unwrap();
+    /// let c: Animation = serde_json::from_str(&animj(2.0)).unwrap();
+    /// assert!(a.bit_identical(&b));
+    /// assert!(!a.bit_identical(&c));
+    /// ```
+    pub fn bit_identical(&self, other: &Animation) -> bool {
+        let a = self.to_animx_bytes().expect("writing to a Vec can't fail");
+        let b = other.to_animx_bytes().expect("writing to a Vec can't fail");
+        a == b
+    }
 
-        self.write_contents(&mut write);
+    fn write_header(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        "AnimX".to_owned().write(writer)?;   // "AnimX" magic header
+        1u32.write(writer)?;                 // Version 01 (wiki says this is supposed to be a byte, but it's an Int / i32)
+        self.tracks.len().write(writer)?;    // Length (wiki says this is supposed to be a 7bit integer, but this is actually a varint)
+        self.global_duration.write(writer)?; // Length of animation in seconds, 0 if there is none
+        self.name.write(writer)?;            // Name of animation, empty if there is none
+        writer.write(&[writer.options().encoding as u8])?; // Encoding flag, only the tracks after it are compressed
+        Ok(())
     }
 
-    fn write_contents(&self, write: &mut dyn FnMut(&[u8])) {
-        "AnimX".to_owned().write(write);    // "AnimX" magic header
-        01u32.write(write);                 // Version 01 (wiki says this is supposed to be a byte, but it's an Int / i32)
-        self.tracks.len().write(write);     // Length (wiki says this is supposed to be a 7bit integer, but this is actually a varint)
-        self.global_duration.write(write);  // Length of animation in seconds
-        self.name.write(write);             // Name of animation
-        write(&[0x00,]);                    // Encoding flag (just none for now)
+    fn write_tracks(&self, writer: &mut AnimXWriter) -> io::Result<()> {
         for track in &self.tracks {
-            track.write(write);             // Tracks
+            track.write(writer)?;            // Tracks
         }
+        Ok(())
     }
 
     /// Function for reading data from an AnimX stream\
-    /// Compression is not yet supported.
+    /// Compressed streams need the ``compression`` feature, see [``AnimXEncoding``]
     /// 
+    /// ```no_run
+    /// use resonite_core::animation::Animation;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let file = std::fs::File::open("anim.animx")?;
+    /// let anim = Animation::from_animx(file)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The stream is buffered internally like [``Animation::write_animx``] does, so it's fine to pass a ``File`` directly.\
+    /// Because of that it might be read past the end of the animation.
+    ///
     /// ```
+    /// use std::io::Read;
     /// use resonite_core::animation::Animation;
-    /// 
-    /// let reader = BufReader::new(/* AnimX */);
-    /// let anim = Animation::from_animx(reader)?;
+    ///
+    /// struct CountingReader<'a>(&'a [u8], usize);
+    /// impl Read for CountingReader<'_> {
+    ///     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    ///         self.1 += 1;
+    ///         self.0.read(buf)
+    ///     }
+    /// }
+    ///
+    /// let frames = vec![1.0f32; 1 << 20];
+    /// let anim = Animation::builder().add_raw_track("n", "p", 0.01, frames).finish();
+    /// let buf = anim.to_animx_bytes().unwrap(); // 4 MiB
+    ///
+    /// let mut reader = CountingReader(&buf, 0);
+    /// let read = Animation::from_animx(&mut reader).unwrap();
+    /// assert_eq!(read.tracks[0].keyframe_count(), 1 << 20);
+    /// assert!(reader.1 < 1000, "{} reads", reader.1);
     /// ```
+    #[cfg(feature = "std")]
     pub fn from_animx(data: impl Read) -> Result<Animation, AnimXError> {
-        let mut output = Animation::default();
-        let mut reader = AnimXReader(data);
+        Self::from_animx_with_options(data, &ReadOptions::default())
+    }
 
-        if reader.read_string()? != "AnimX" { Err(AnimXError::IncorrectHeader)? }
-        if reader.read_i32()? != 1 { Err(AnimXError::UnsupportedVersion)? }
+    /// Same as [``Animation::from_animx``], but with [``ReadOptions``] to control how lenient the reader is
+    #[cfg(feature = "std")]
+    pub fn from_animx_with_options(data: impl Read, options: &ReadOptions) -> Result<Animation, AnimXError> {
+        Self::read_animx(BufReader::new(data), options)
+    }
+
+    /// Reads an AnimX file that's already in memory, skipping the buffering [``Animation::from_animx``] does
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"name": "Slice", "tracks": [{"trackType": "Discrete", "valueType": "float",
+    ///     "data": {"node": "n", "property": "p", "keyframes": [{"time": 0.0, "value": 1.0}, {"time": 1.0, "value": 2.0}]}}]}"#).unwrap();
+    /// let buf = anim.to_animx_bytes().unwrap();
+    ///
+    /// let sliced = Animation::from_animx_slice(&buf).unwrap();
+    /// assert!(sliced.bit_identical(&Animation::from_animx(&buf[..]).unwrap()));
+    /// assert!(sliced.bit_identical(&anim));
+    /// ```
+    pub fn from_animx_slice(data: &[u8]) -> Result<Animation, AnimXError> {
+        Self::from_animx_slice_with_options(data, &ReadOptions::default())
+    }
+
+    /// Same as [``Animation::from_animx_slice``], but with [``ReadOptions``] to control how lenient the reader is
+    ///
+    /// ```
+    /// use resonite_core::animation::{AnimXError, Animation, ReadOptions};
+    ///
+    /// let mut buf = Animation::builder().add_raw_track("n", "p", 0.1, vec![1.0f32]).finish().to_animx_bytes().unwrap();
+    /// buf.push(0);
+    /// let strict = ReadOptions { reject_trailing_data: true, ..Default::default() };
+    /// assert!(matches!(Animation::from_animx_slice_with_options(&buf, &strict), Err(AnimXError::TrailingData)));
+    /// ```
+    pub fn from_animx_slice_with_options(data: &[u8], options: &ReadOptions) -> Result<Animation, AnimXError> {
+        Self::read_animx(data, options)
+    }
+
+    /// Reads an AnimX stream without any extra buffering, every value is a separate read
+    fn read_animx(data: impl Read, options: &ReadOptions) -> Result<Animation, AnimXError> {
+        let mut reader = AnimXReader { inner: data, options: options.clone(), position: 0 };
+        let header = AnimXHeader::read(&mut reader)?;
+        // Offsets keep counting from the header, compressed tracks count as if they weren't
+        let mut reader = AnimXReader { inner: header.encoding.decoder(reader.inner, options)?, options: options.clone(), position: reader.position };
+        let mut output = Animation {
+            name: header.name,
+            global_duration: header.global_duration,
+            tracks: Vec::with_capacity(header.track_count.min(PREALLOCATION_LIMIT)),
+            extra: serde_json::Map::new(),
+        };
+
+        for _ in 0..header.track_count {
+            output.tracks.push(Self::read_track(&mut reader)?);
+        }
+        if options.reject_trailing_data && reader.inner.read(&mut [0])? > 0 {
+            return Err(AnimXError::TrailingData);
+        }
+        output.handle_unsorted(options)?;
+
+        Ok(output)
+    }
+
+    /// Reads a single track, everything from its track type up to the end of its keyframes
+    fn read_track<R: Read>(reader: &mut AnimXReader<R>) -> Result<Box<dyn TrackTrait>, AnimXError> {
+        let track_type: TrackType = reader.read_enum(|offset, byte| AnimXError::IncorrectTrackType { offset, byte })?;
+        let value_type: ValueType = reader.read_enum(|offset, byte| AnimXError::IncorrectValueType { offset, byte })?;
 
-        let tracks = reader.read_varint()?;
-        output.global_duration = Some(reader.read_f32()?);
-        output.name = Some(reader.read_string()?);
-
-        if reader.read_u8()? != 0 { Err(AnimXError::UnsupportedEncoding)? }
-
-        for _ in 0..tracks {
-            let track_type: TrackType = reader.read_u8()?.try_into().map_err(|_| AnimXError::IncorrectTrackType)?;
-            let value_type: ValueType = reader.read_u8()?.try_into().map_err(|_| AnimXError::IncorrectValueType)?;
-
-            let node = Some(reader.read_string()?);
-            let property = Some(reader.read_string()?);
-            let frames = reader.read_varint()?;
-
-            match track_type {
-                TrackType::Raw => {
-                    let interval = Some(reader.read_f32()?);
-                    metamatch::metamatch!(match value_type {
-                        #[expand(for T in [
-                            Byte, Ushort, Ulong, Sbyte, Short,
-                            Bool, Bool2, Bool3, Bool4,
-                            Int, Int2, Int3, Int4,
-                            Uint, Uint2, Uint3, Uint4,
-                            Long, Long2, Long3, Long4,
-                            Float, Float2, Float3, Float4,
-                            FloatQ, Float2x2, Float3x3, Float4x4,
-                            Double, Double2, Double3, Double4,
-                            DoubleQ, Double2x2, Double3x3, Double4x4,
-                            Color, Color32, OptString,
-                        ])]
-                        ValueType::T => {
-                            let mut keyframes = Vec::new();
-                            for _ in 0..frames {
-                                keyframes.push(T::read(&mut reader)?);
+        let node = reader.read_target()?;
+        let property = reader.read_target()?;
+        let frames = reader.read_varint()?;
+
+        let track: Box<dyn TrackTrait> = match track_type {
+            TrackType::Raw => {
+                let interval = Some(reader.read_f32()?);
+                metamatch::metamatch!(match value_type {
+                    #[expand(for T in [
+                        Byte, Ushort, Ulong, Sbyte, Short,
+                        Bool, Bool2, Bool3, Bool4,
+                        Int, Int2, Int3, Int4,
+                        Uint, Uint2, Uint3, Uint4,
+                        Long, Long2, Long3, Long4,
+                        Float, Float2, Float3, Float4,
+                        FloatQ, Float2x2, Float3x3, Float4x4,
+                        Double, Double2, Double3, Double4,
+                        DoubleQ, Double2x2, Double3x3, Double4x4,
+                        Color, Color32, OptString,
+                    ])]
+                    ValueType::T => {
+                        let mut keyframes = Vec::with_capacity(frames.min(PREALLOCATION_LIMIT));
+                        for _ in 0..frames {
+                            keyframes.push(T::read(reader)?);
+                        }
+                        Box::new(
+                            Track{
+                                track_type,
+                                value_type,
+                                data: RawData {
+                                    node,
+                                    property,
+                                    interval,
+                                    keyframes,
+                                },
                             }
-                            output.tracks.push(
-                                Box::new(
-                                    Track{
-                                        track_type,
-                                        value_type,
-                                        data: RawData {
-                                            node,
-                                            property,
-                                            interval,
-                                            keyframes,
-                                        },
-                                    }
-                                )
-                            );
-                        },
-                    })
-                },
-                TrackType::Discrete => {
-                    metamatch::metamatch!(match value_type {
-                        #[expand(for T in [
-                            Byte, Ushort, Ulong, Sbyte, Short,
-                            Bool, Bool2, Bool3, Bool4,
-                            Int, Int2, Int3, Int4,
-                            Uint, Uint2, Uint3, Uint4,
-                            Long, Long2, Long3, Long4,
-                            Float, Float2, Float3, Float4,
-                            FloatQ, Float2x2, Float3x3, Float4x4,
-                            Double, Double2, Double3, Double4,
-                            DoubleQ, Double2x2, Double3x3, Double4x4,
-                            Color, Color32, OptString,
-                        ])]
-                        ValueType::T => {
-                            let mut keyframes = Vec::new();
-                            for _ in 0..frames {
-                                let time = reader.read_f32()?;
-                                let value = T::read(&mut reader)?;
-                                keyframes.push(DiscreteKeyframe{time, value});
+                        )
+                    },
+                })
+            },
+            TrackType::Discrete => {
+                metamatch::metamatch!(match value_type {
+                    #[expand(for T in [
+                        Byte, Ushort, Ulong, Sbyte, Short,
+                        Bool, Bool2, Bool3, Bool4,
+                        Int, Int2, Int3, Int4,
+                        Uint, Uint2, Uint3, Uint4,
+                        Long, Long2, Long3, Long4,
+                        Float, Float2, Float3, Float4,
+                        FloatQ, Float2x2, Float3x3, Float4x4,
+                        Double, Double2, Double3, Double4,
+                        DoubleQ, Double2x2, Double3x3, Double4x4,
+                        Color, Color32, OptString,
+                    ])]
+                    ValueType::T => {
+                        let mut keyframes = Vec::with_capacity(frames.min(PREALLOCATION_LIMIT));
+                        for _ in 0..frames {
+                            let time = reader.read_f32()?;
+                            let value = T::read(reader)?;
+                            keyframes.push(DiscreteKeyframe{time, value});
+                        }
+                        Box::new(
+                            Track{
+                                track_type,
+                                value_type,
+                                data: DiscreteData {
+                                    node,
+                                    property,
+                                    keyframes,
+                                },
                             }
-                            output.tracks.push(
-                                Box::new(
-                                    Track{
-                                        track_type,
-                                        value_type,
-                                        data: DiscreteData {
-                                            node,
-                                            property,
-                                            keyframes,
-                                        },
-                                    }
-                                )
-                            );
-                        },
-                    })
-                },
-                TrackType::Curve => {
-                    let info = Bool2::read(&mut reader)?;
-                    let mut interpolations = Vec::new();
-                    for _ in if info.x {0..frames} else {0..1} {
-                        interpolations.push(Interpolation::try_from(reader.read_u8()?).map_err(|_| AnimXError::IncorrectInterpolationType)?);
-                    }
-                    
-                    metamatch::metamatch!(match value_type {
-                        #[expand(for T in [
-                            Byte, Ushort, Ulong, Sbyte, Short,
-                            Bool, Bool2, Bool3, Bool4,
-                            Int, Int2, Int3, Int4,
-                            Uint, Uint2, Uint3, Uint4,
-                            Long, Long2, Long3, Long4,
-                            Float, Float2, Float3, Float4,
-                            FloatQ, Float2x2, Float3x3, Float4x4,
-                            Double, Double2, Double3, Double4,
-                            DoubleQ, Double2x2, Double3x3, Double4x4,
-                            Color, Color32, OptString,
-                        ])]
-                        ValueType::T => {
-                            let mut keyframes = Vec::new();
-                            for i in 0..frames {
-                                let time = reader.read_f32()?;
-                                let value = T::read(&mut reader)?;
-                                let interpolation = interpolations[if info.x {i} else {0}];
-                                keyframes.push(CurveKeyframe{time, value, interpolation, left_tangent: None, right_tangent: None});
+                        )
+                    },
+                })
+            },
+            TrackType::Curve => {
+                let info = Bool2::read(reader)?;
+                // Tangents only make sense for values that can be interpolated, reading them for anything else would desync the stream
+                if info.y && !value_type.is_interpolatable() {
+                    Err(AnimXError::UnexpectedTangents(value_type))?
+                }
+                let mut interpolations = Vec::new();
+                for _ in if info.x {0..frames} else {0..1} {
+                    interpolations.push(reader.read_enum(|offset, byte| AnimXError::IncorrectInterpolationType { offset, byte })?);
+                }
+                
+                metamatch::metamatch!(match value_type {
+                    #[expand(for T in [
+                        Byte, Ushort, Ulong, Sbyte, Short,
+                        Bool, Bool2, Bool3, Bool4,
+                        Int, Int2, Int3, Int4,
+                        Uint, Uint2, Uint3, Uint4,
+                        Long, Long2, Long3, Long4,
+                        Float, Float2, Float3, Float4,
+                        FloatQ, Float2x2, Float3x3, Float4x4,
+                        Double, Double2, Double3, Double4,
+                        DoubleQ, Double2x2, Double3x3, Double4x4,
+                        Color, Color32, OptString,
+                    ])]
+                    ValueType::T => {
+                        let mut keyframes = Vec::with_capacity(frames.min(PREALLOCATION_LIMIT));
+                        for i in 0..frames {
+                            let time = reader.read_f32()?;
+                            let value = T::read(reader)?;
+                            let interpolation = interpolations[if info.x {i} else {0}];
+                            keyframes.push(CurveKeyframe{time, value, interpolation, left_tangent: None, right_tangent: None});
+                        }
+                        if info.y {
+                            for keyframe in &mut keyframes {
+                                keyframe.left_tangent = Some((reader.read_f32()?, T::read(reader)?));
+                                keyframe.right_tangent = Some((reader.read_f32()?, T::read(reader)?));
                             }
-                            if info.y {
-                                for i in 0..frames {
-                                    let keyframe = &mut keyframes[i];
-                                    keyframe.left_tangent = Some(T::read(&mut reader)?);
-                                    keyframe.right_tangent = Some(T::read(&mut reader)?);
-                                }
+                        }
+                        Box::new(
+                            Track{
+                                track_type,
+                                value_type,
+                                data: CurveData {
+                                    node,
+                                    property,
+                                    keyframes,
+                                },
                             }
-                            output.tracks.push(
-                                Box::new(
-                                    Track{
-                                        track_type,
-                                        value_type,
-                                        data: CurveData {
-                                            node,
-                                            property,
-                                            keyframes,
-                                        },
-                                    }
-                                )
-                            );
-                        },
-                    })
-                },
-                _ => unreachable!(),
+                        )
+                    },
+                })
+            },
+            TrackType::Bezier => {
+                metamatch::metamatch!(match value_type {
+                    #[expand(for T in [
+                        Byte, Ushort, Ulong, Sbyte, Short,
+                        Bool, Bool2, Bool3, Bool4,
+                        Int, Int2, Int3, Int4,
+                        Uint, Uint2, Uint3, Uint4,
+                        Long, Long2, Long3, Long4,
+                        Float, Float2, Float3, Float4,
+                        FloatQ, Float2x2, Float3x3, Float4x4,
+                        Double, Double2, Double3, Double4,
+                        DoubleQ, Double2x2, Double3x3, Double4x4,
+                        Color, Color32, OptString,
+                    ])]
+                    ValueType::T => {
+                        let mut keyframes = Vec::with_capacity(frames.min(PREALLOCATION_LIMIT));
+                        for _ in 0..frames {
+                            let time = reader.read_f32()?;
+                            let value = T::read(reader)?;
+                            let left_control = T::read(reader)?;
+                            let right_control = T::read(reader)?;
+                            keyframes.push(BezierKeyframe{time, value, left_control, right_control});
+                        }
+                        Box::new(
+                            Track{
+                                track_type,
+                                value_type,
+                                data: BezierData {
+                                    node,
+                                    property,
+                                    keyframes,
+                                },
+                            }
+                        )
+                    },
+                })
+            },
+        };
+
+        Ok(track)
+    }
+
+    /// Applies [``ReadOptions::on_unsorted``] to every track that was read
+    fn handle_unsorted(&mut self, options: &ReadOptions) -> Result<(), AnimXError> {
+        for (index, track) in self.tracks.iter_mut().enumerate() {
+            match options.on_unsorted {
+                UnsortedPolicy::Accept => {},
+                UnsortedPolicy::Sort => track.sort_keyframes(),
+                UnsortedPolicy::Error => if !track.is_sorted() { Err(AnimXError::UnsortedKeyframes(index))? },
             }
         }
 
-        Ok(output)
+        Ok(())
+    }
+}
+
+/// Counts read from a stream are only trusted this far when reserving memory up front, so a corrupt count can't allocate gigabytes right away
+const PREALLOCATION_LIMIT: usize = 1 << 16;
+
+/// Everything in front of the tracks of an AnimX stream, see [``Animation::read_header``]
+#[derive(Debug, Clone)]
+pub struct AnimXHeader {
+    /// AnimX has no way to leave out the name, so a missing one is written as an empty string and an empty string is read as ``None``
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim = Animation::default();
+    /// let read = Animation::from_animx(&anim.to_animx_bytes().unwrap()[..]).unwrap();
+    /// assert_eq!(read.name, None);
+    /// assert_eq!(read.global_duration, None);
+    ///
+    /// let anim = Animation { name: Some("Wave".into()), global_duration: Some(2.0), ..Default::default() };
+    /// let read = Animation::from_animx(&anim.to_animx_bytes().unwrap()[..]).unwrap();
+    /// assert_eq!(read.name.as_deref(), Some("Wave"));
+    /// assert_eq!(read.global_duration, Some(2.0));
+    /// ```
+    pub name: Option<String>,
+    /// Same as the name, a missing duration is written as 0 and 0 is read as ``None``
+    pub global_duration: Option<f32>,
+    pub track_count: usize,
+    pub encoding: AnimXEncoding,
+}
+
+/// How the tracks of an AnimX stream are compressed, the header (up to and including this flag) never is
+///
+/// Only ``Plain`` can be read without the ``compression`` feature, the others error with [``AnimXError::UnsupportedEncoding``]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AnimXEncoding {
+    #[default]
+    Plain,
+    /// Raw deflate, without a zlib or gzip wrapper
+    Deflate,
+    /// LZMA "alone" format, the properties byte, dictionary size and uncompressed size in front of the data
+    Lzma,
+}
+
+impl TryFrom<u8> for AnimXEncoding {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Plain),
+            1 => Ok(Self::Deflate),
+            2 => Ok(Self::Lzma),
+            _ => Err(()),
+        }
+    }
+}
+
+impl AnimXEncoding {
+    /// Wraps the rest of a stream, right after the encoding flag, into a reader of the uncompressed tracks
+    #[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+    fn decoder<'a>(self, inner: impl Read + 'a, options: &ReadOptions) -> Result<Box<dyn Read + 'a>, AnimXError> {
+        match self {
+            Self::Plain => Ok(Box::new(inner)),
+            #[cfg(feature = "compression")]
+            encoding => compression::decoder(encoding, inner, options.max_decompressed_len),
+            #[cfg(not(feature = "compression"))]
+            _ => Err(AnimXError::UnsupportedEncoding),
+        }
+    }
+}
+
+impl AnimXHeader {
+    fn read<R: Read>(reader: &mut AnimXReader<R>) -> Result<Self, AnimXError> {
+        if reader.read_string()? != "AnimX" { Err(AnimXError::IncorrectHeader)? }
+        if reader.read_i32()? != 1 { Err(AnimXError::UnsupportedVersion)? }
+
+        let track_count = if reader.options().legacy_track_count { reader.read_u8()? as usize } else { reader.read_varint()? };
+        // There are no null flags, the writer uses 0 and "" for missing values
+        let global_duration = Some(reader.read_f32()?).filter(|d| *d != 0.0);
+        let name = Some(reader.read_string()?).filter(|n| !n.is_empty());
+
+        let encoding = reader.read_u8()?.try_into().map_err(|_| AnimXError::UnsupportedEncoding)?;
+
+        Ok(Self { name, global_duration, track_count, encoding })
+    }
+}
+
+impl Animation {
+    /// Reads just the header of an AnimX stream, leaving the tracks unread
+    #[cfg(feature = "std")]
+    pub fn read_header(data: impl Read) -> Result<AnimXHeader, AnimXError> {
+        AnimXHeader::read(&mut AnimXReader { inner: data, options: ReadOptions::default(), position: 0 })
+    }
+
+    /// Reads the header of an AnimX stream and seeks back to where it started, so the whole animation can be read afterwards
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"name": "Wave", "globalDuration": 2.0, "tracks": []}"#).unwrap();
+    /// let mut buf = Vec::new();
+    /// anim.write_animx(&mut buf).unwrap();
+    ///
+    /// let mut cursor = Cursor::new(buf);
+    /// let header = Animation::peek_header(&mut cursor).unwrap();
+    /// assert_eq!(header.name.as_deref(), Some("Wave"));
+    /// let anim = Animation::from_animx(&mut cursor).unwrap();
+    /// assert_eq!(anim.global_duration, Some(2.0));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn peek_header<R: Read + Seek>(data: &mut R) -> Result<AnimXHeader, AnimXError> {
+        let start = data.stream_position()?;
+        let header = Self::read_header(&mut *data);
+        data.seek(SeekFrom::Start(start))?;
+        header
+    }
+
+    /// Reads the header of a stream that can't seek, returning it along with a reader that still starts at the very beginning
+    ///
+    /// The bytes of the header are kept around and replayed, so the returned reader can be passed to [``Animation::from_animx``]
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"name": "Wave", "globalDuration": 2.0, "tracks": []}"#).unwrap();
+    /// let mut buf = Vec::new();
+    /// anim.write_animx(&mut buf).unwrap();
+    ///
+    /// let (header, rest) = Animation::read_header_buffered(&buf[..]).unwrap();
+    /// assert_eq!(header.track_count, 0);
+    /// assert_eq!(Animation::from_animx(rest).unwrap().name.as_deref(), Some("Wave"));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn read_header_buffered<R: Read>(data: R) -> Result<(AnimXHeader, impl Read), AnimXError> {
+        let mut reader = AnimXReader { inner: RecordingReader { inner: data, consumed: Vec::new() }, options: ReadOptions::default(), position: 0 };
+        let header = AnimXHeader::read(&mut reader)?;
+        let RecordingReader { inner, consumed } = reader.inner;
+        Ok((header, Cursor::new(consumed).chain(inner)))
+    }
+}
+
+/// Keeps a copy of everything read through it
+#[cfg(feature = "std")]
+struct RecordingReader<R> where R: Read {
+    inner: R,
+    consumed: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Read for RecordingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.consumed.extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
+
+/// Options for [``Animation::from_animx_with_options``]
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    /// What to do with tracks whose keyframes aren't sorted by time
+    pub on_unsorted: UnsortedPolicy,
+    /// How the values of string tracks were written, this has to match the writer since the two can't be told apart
+    pub opt_string_encoding: OptStringEncoding,
+    /// Read the track count as a single byte instead of a varint
+    ///
+    /// The wiki describes the track count as a "7bit integer", every file seen so far (and the writer) uses a varint though,
+    /// which only differs once there are more than 127 tracks. This is for files that actually follow the wiki.
+    ///
+    /// ```
+    /// use resonite_core::animation::*;
+    ///
+    /// let tracks = vec![r#"{"trackType": "Discrete", "valueType": "float", "data": {"node": "n", "property": "p", "keyframes": []}}"#; 128];
+    /// let anim: Animation = serde_json::from_str(&format!(r#"{{"tracks": [{}]}}"#, tracks.join(","))).unwrap();
+    ///
+    /// let mut buf = Vec::new();
+    /// anim.write_animx(&mut buf).unwrap();
+    /// assert_eq!(buf[10..12], [0x80, 0x01]); // Right after the header and version
+    /// assert_eq!(Animation::from_animx(&buf[..]).unwrap().tracks.len(), 128);
+    /// ```
+    pub legacy_track_count: bool,
+    /// Longest string in bytes that will be read, anything longer errors with [``AnimXError::LimitExceeded``] before allocating
+    ///
+    /// Defaults to 16 MiB, which keeps a corrupt or malicious length from allocating gigabytes.
+    ///
+    /// ```
+    /// use resonite_core::animation::*;
+    ///
+    /// let mut buf = vec![5];
+    /// buf.extend(b"AnimX");
+    /// buf.extend(1i32.to_le_bytes());
+    /// buf.push(0); // No tracks
+    /// buf.extend(0f32.to_le_bytes());
+    /// buf.extend([0xff, 0xff, 0xff, 0xff, 0x0f]); // A name claiming to be 4 GiB long
+    ///
+    /// let options = ReadOptions { max_string_len: 1024, ..Default::default() };
+    /// let result = Animation::from_animx_with_options(&buf[..], &options);
+    /// assert!(matches!(result, Err(AnimXError::LimitExceeded(0xffffffff))));
+    /// ```
+    pub max_string_len: usize,
+    /// How the node & property of every track were written, see [``WriteOptions::target_encoding``]
+    ///
+    /// ```
+    /// use resonite_core::animation::*;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [{"trackType": "Discrete", "valueType": "float",
+    ///     "data": {"node": null, "property": "p", "keyframes": []}}]}"#).unwrap();
+    ///
+    /// for target_encoding in [OptStringEncoding::Flagged, OptStringEncoding::LengthPrefixed] {
+    ///     let mut buf = Vec::new();
+    ///     anim.write_animx_with_options(&mut buf, &WriteOptions { target_encoding, ..Default::default() }).unwrap();
+    ///     let read = Animation::from_animx_with_options(&buf[..], &ReadOptions { target_encoding, ..Default::default() }).unwrap();
+    ///     assert_eq!(read.tracks[0].node(), None);
+    ///     assert_eq!(read.tracks[0].property(), Some("p"));
+    /// }
+    /// ```
+    pub target_encoding: OptStringEncoding,
+    /// Fail with [``AnimXError::TrailingData``] if anything follows the last track
+    ///
+    /// Off by default, so files with data a newer writer appends still read.
+    /// Turning it on catches a wrong track count, or two files glued together.
+    ///
+    /// ```
+    /// use resonite_core::animation::*;
+    ///
+    /// let anim = Animation::builder().add_raw_track("n", "p", 0.1, vec![1.0f32, 2.0]).finish();
+    /// let mut buf = anim.to_animx_bytes().unwrap();
+    /// buf.push(0x2a);
+    ///
+    /// let strict = ReadOptions { reject_trailing_data: true, ..Default::default() };
+    /// assert!(Animation::from_animx(&buf[..]).is_ok());
+    /// assert!(matches!(Animation::from_animx_with_options(&buf[..], &strict), Err(AnimXError::TrailingData)));
+    /// assert!(Animation::from_animx_with_options(&buf[..buf.len() - 1], &strict).is_ok());
+    /// ```
+    pub reject_trailing_data: bool,
+    /// Most bytes the tracks of a compressed stream can decompress to, anything more errors with an [``AnimXError::IoError``]
+    ///
+    /// Defaults to 256 MiB, a few kilobytes of LZMA or deflate can otherwise expand to gigabytes.
+    /// Plain streams aren't affected.
+    pub max_decompressed_len: usize,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            on_unsorted: UnsortedPolicy::default(),
+            opt_string_encoding: OptStringEncoding::default(),
+            legacy_track_count: false,
+            max_string_len: 16 << 20,
+            target_encoding: OptStringEncoding::LengthPrefixed,
+            reject_trailing_data: false,
+            max_decompressed_len: 256 << 20,
+        }
+    }
+}
+
+/// Options for [``Animation::write_animx_with_options``]
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    /// How to write the values of string tracks
+    pub opt_string_encoding: OptStringEncoding,
+    /// How to compress the tracks, anything but ``Plain`` needs the ``compression`` feature\
+    /// The header stays uncompressed either way, so [``Animation::read_header``] works on every stream
+    pub encoding: AnimXEncoding,
+    /// How to write the node & property of every track
+    ///
+    /// Every file seen so far writes them as plain strings, which can't tell a missing node from an empty one,
+    /// so both are written as an empty string and an empty string is read back as ``None``.\
+    /// ``Flagged`` writes a presence byte in front of them instead, in case a file turns up that does that
+    pub target_encoding: OptStringEncoding,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            opt_string_encoding: OptStringEncoding::default(),
+            encoding: AnimXEncoding::default(),
+            target_encoding: OptStringEncoding::LengthPrefixed,
+        }
     }
 }
 
+/// Options for [``Animation::to_animj_string_with_options``]
+#[derive(Debug, Default, Clone)]
+pub struct AnimJOptions {
+    /// How ``f32`` values are written, see [``AnimJFloats``]
+    pub floats: AnimJFloats,
+}
+
+/// How ``f32``s (times, and the values of every type but doubles) are written to AnimJ
+///
+/// Both read back to the exact same bits, so AnimX -> AnimJ -> AnimX gives the same file either way.
+/// Doubles are always written in full, they only read back exactly because ``serde_json``'s ``float_roundtrip`` parsing is turned on
+/// (the default parser can be a bit off), which makes reading AnimJ a little slower.\
+/// The difference is how the numbers look:
+///
+/// ```
+/// use resonite_core::animation::*;
+///
+/// let anim = Animation::builder().add_raw_track("n", "p", 0.1, vec![0.1f32, 1.0 / 3.0]).finish();
+///
+/// let wide = anim.to_animj_string().unwrap();
+/// assert!(wide.contains("[0.10000000149011612,0.3333333432674408]"));
+///
+/// let shortest = anim.to_animj_string_with_options(&AnimJOptions { floats: AnimJFloats::Shortest }).unwrap();
+/// assert!(shortest.contains(r#""interval":0.1,"keyframes":[0.1,0.33333334]"#));
+///
+/// for json in [wide, shortest] {
+///     assert!(Animation::from_animj_str(&json).unwrap().bit_identical(&anim));
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AnimJFloats {
+    /// Widened to ``f64`` and written with up to 17 digits, which is what ``serde_json`` does on its own
+    ///
+    /// ``0.1`` comes out as ``0.10000000149011612``, the exact value of the ``f32`` closest to ``0.1``.
+    /// Noisy to read, but any JSON parser reading it as a double and narrowing it gets the same bits back.
+    #[default]
+    Wide,
+    /// The fewest digits that still read back as the same ``f32``, so ``0.1`` stays ``0.1``
+    ///
+    /// Much nicer to read & diff, and close to what Resonite writes.
+    /// Every value is formatted and parsed once more to make sure it reads back the same, the rare one that wouldn't is written wide instead.
+    Shortest,
+}
+
+/// How the values of string tracks ([``OptString``]) are encoded, also used for node & property names by [``WriteOptions::target_encoding``]
+///
+/// Empty strings end up as a single ``0x00`` byte either way, so both encodings read back to the same empty string
+///
+/// ```
+/// use resonite_core::animation::*;
+///
+/// let anim: Animation = serde_json::from_str(r#"{"tracks": [{"trackType": "Discrete", "valueType": "string",
+///     "data": {"node": "n", "property": "p", "keyframes": [{"time": 0.0, "value": ""}]}}]}"#).unwrap();
+///
+/// for opt_string_encoding in [OptStringEncoding::Flagged, OptStringEncoding::LengthPrefixed] {
+///     let mut buf = Vec::new();
+///     anim.write_animx_with_options(&mut buf, &WriteOptions { opt_string_encoding, ..Default::default() }).unwrap();
+///     let read = Animation::from_animx_with_options(&buf[..], &ReadOptions { opt_string_encoding, ..Default::default() }).unwrap();
+///     assert_eq!(serde_json::to_value(&read).unwrap()["tracks"], serde_json::to_value(&anim).unwrap()["tracks"]);
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OptStringEncoding {
+    /// A presence byte (``0x00`` for null, ``0x01`` otherwise) followed by the length prefixed string
+    #[default]
+    Flagged,
+    /// Just the length prefixed string, without a presence byte
+    LengthPrefixed,
+}
+
+/// How to handle keyframes that aren't sorted by time, which breaks sampling
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UnsortedPolicy {
+    /// Keep the keyframes as they are
+    #[default]
+    Accept,
+    /// Sort the keyframes by time (keeping the order of keyframes with the same time)
+    Sort,
+    /// Fail with [``AnimXError::UnsortedKeyframes``]
+    Error,
+}
+
+/// Everything that can go wrong reading or writing AnimX
+///
+/// Displays as a short lowercase message, the io & utf-8 errors are available through ``source``:
+///
+/// ```
+/// use std::error::Error;
+/// use resonite_core::animation::AnimXError;
+///
+/// assert_eq!(AnimXError::UnsupportedVersion.to_string(), "unsupported AnimX version");
+/// assert_eq!(AnimXError::LimitExceeded(4096).to_string(), "a string of 4096 bytes is longer than the limit");
+/// assert!(AnimXError::IncorrectTrackType { offset: 15, byte: 7 }.source().is_none());
+///
+/// let error = AnimXError::from(std::io::Error::other("disk on fire"));
+/// assert_eq!(error.to_string(), "io error: disk on fire");
+/// assert_eq!(error.source().unwrap().to_string(), "disk on fire");
+///
+/// let error: Box<dyn Error> = AnimXError::from(String::from_utf8(vec![0xff]).unwrap_err()).into();
+/// assert!(error.source().unwrap().is::<std::string::FromUtf8Error>());
+/// ```
 #[derive(Debug)]
 pub enum AnimXError {
     IncorrectHeader,
     UnsupportedVersion,
     UnsupportedEncoding,
-    IncorrectTrackType,
-    IncorrectValueType,
-    IncorrectInterpolationType,
+    /// The byte at ``offset`` isn't a track type
+    ///
+    /// Offsets count from the start of the stream, for compressed streams they count as if the tracks weren't compressed
+    ///
+    /// ```
+    /// use resonite_core::animation::*;
+    ///
+    /// let anim = Animation::builder().add_raw_track("n", "p", 0.1, vec![1.0f32, 2.0]).finish();
+    /// let mut buf = anim.to_animx_bytes().unwrap();
+    ///
+    /// // The value type comes right after the track type, which is the first byte after the header
+    /// let header = Animation::builder().finish().to_animx_bytes().unwrap().len();
+    /// buf[header + 1] = 0xee;
+    /// let error = Animation::from_animx(&buf[..]).unwrap_err();
+    /// assert!(matches!(error, AnimXError::IncorrectValueType { offset, byte: 0xee } if offset == header as u64 + 1));
+    /// assert_eq!(error.to_string(), format!("incorrect value type byte 238 at offset {}", header + 1));
+    /// ```
+    IncorrectTrackType { offset: u64, byte: u8 },
+    /// The byte at ``offset`` isn't a value type, see [``AnimXError::IncorrectTrackType``]
+    IncorrectValueType { offset: u64, byte: u8 },
+    /// The byte at ``offset`` isn't an interpolation, see [``AnimXError::IncorrectTrackType``]
+    IncorrectInterpolationType { offset: u64, byte: u8 },
+    /// A curve track of bools or strings claimed to have tangents
+    UnexpectedTangents(ValueType),
+    /// The keyframes of the track at this index aren't sorted by time, see [``UnsortedPolicy``]
+    UnsortedKeyframes(usize),
+    /// A string declared this many bytes, more than [``ReadOptions::max_string_len``] allows
+    LimitExceeded(usize),
+    /// A length or count didn't fit in a ``usize``, the stream is corrupt
+    ///
+    /// ```
+    /// use resonite_core::animation::*;
+    ///
+    /// let mut buf = vec![5];
+    /// buf.extend(b"AnimX");
+    /// buf.extend(1i32.to_le_bytes());
+    /// buf.extend([0xff; 10]); // A track count that never ends
+    ///
+    /// assert!(matches!(Animation::from_animx(&buf[..]), Err(AnimXError::VarintOverflow)));
+    /// ```
+    VarintOverflow,
+    /// The stream ended in the middle of a read of this many bytes, the file is probably cut short
+    ///
+    /// ```
+    /// use resonite_core::animation::*;
+    ///
+    /// let anim = Animation::builder().add_raw_track("n", "p", 0.1, vec![1.0f32, 2.0]).finish();
+    /// let buf = anim.to_animx_bytes().unwrap();
+    ///
+    /// // Cut right after the header, the track type byte is missing
+    /// let header = Animation::builder().finish().to_animx_bytes().unwrap().len();
+    /// assert_eq!(Animation::read_header(&buf[..header]).unwrap().track_count, 1);
+    /// assert!(matches!(Animation::from_animx(&buf[..header]), Err(AnimXError::UnexpectedEof(1))));
+    ///
+    /// // Cut in the middle of the last frame
+    /// assert!(matches!(Animation::from_animx(&buf[..buf.len() - 1]), Err(AnimXError::UnexpectedEof(4))));
+    /// ```
+    UnexpectedEof(usize),
+    /// There's more data after the last track, see [``ReadOptions::reject_trailing_data``]
+    TrailingData,
+    /// Only there with the ``std`` feature, without it AnimX is only read from slices and written to ``Vec``s, which can't fail like this
+    #[cfg(feature = "std")]
     IoError(std::io::Error),
-    FromUtf8Error(std::string::FromUtf8Error),
+    FromUtf8Error(FromUtf8Error),
+}
+
+impl core::fmt::Display for AnimXError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::IncorrectHeader => f.write_str("not an AnimX stream, the header is incorrect"),
+            Self::UnsupportedVersion => f.write_str("unsupported AnimX version"),
+            Self::UnsupportedEncoding => f.write_str("unsupported AnimX encoding"),
+            Self::IncorrectTrackType { offset, byte } => write!(f, "incorrect track type byte {byte} at offset {offset}"),
+            Self::IncorrectValueType { offset, byte } => write!(f, "incorrect value type byte {byte} at offset {offset}"),
+            Self::IncorrectInterpolationType { offset, byte } => write!(f, "incorrect interpolation byte {byte} at offset {offset}"),
+            Self::UnexpectedTangents(value_type) => write!(f, "a curve of {value_type:?} values can't have tangents"),
+            Self::UnsortedKeyframes(track) => write!(f, "the keyframes of track {track} aren't sorted by time"),
+            Self::LimitExceeded(len) => write!(f, "a string of {len} bytes is longer than the limit"),
+            Self::VarintOverflow => f.write_str("a varint is too large to be a length"),
+            Self::UnexpectedEof(len) => write!(f, "the stream ended in the middle of reading {len} bytes"),
+            Self::TrailingData => f.write_str("data after the last track"),
+            #[cfg(feature = "std")]
+            Self::IoError(e) => write!(f, "io error: {e}"),
+            Self::FromUtf8Error(e) => write!(f, "a string isn't valid utf-8: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for AnimXError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            Self::IoError(e) => Some(e),
+            Self::FromUtf8Error(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for AnimXError {
     fn from(e: std::io::Error) -> Self {
         Self::IoError(e)
     }
 }
 
-impl From<std::string::FromUtf8Error> for AnimXError {
-    fn from(e: std::string::FromUtf8Error) -> Self {
+impl From<FromUtf8Error> for AnimXError {
+    fn from(e: FromUtf8Error) -> Self {
         Self::FromUtf8Error(e)
     }
 }
 
-pub(crate) struct AnimXReader<R>(R) where R: Read;
+/// Something wrong with an AnimJ file, either broken JSON or an animation that doesn't fit the format
+#[derive(Debug)]
+pub struct AnimJError(pub serde_json::Error);
+
+impl core::fmt::Display for AnimJError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "invalid AnimJ: {}", self.0)
+    }
+}
+
+impl core::error::Error for AnimJError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl From<serde_json::Error> for AnimJError {
+    fn from(e: serde_json::Error) -> Self {
+        Self(e)
+    }
+}
+
+pub(crate) struct AnimXWriter<'a> {
+    inner: &'a mut dyn Write,
+    options: &'a WriteOptions,
+    written: u64,
+}
+
+impl AnimXWriter<'_> {
+    pub(crate) fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.inner.write_all(bytes)?;
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+
+    pub(crate) fn options(&self) -> &WriteOptions {
+        self.options
+    }
+}
+
+pub(crate) struct AnimXReader<R> where R: Read {
+    inner: R,
+    options: ReadOptions,
+    /// Bytes read so far, for the offsets in errors
+    position: u64,
+}
 
 impl<R: Read> AnimXReader<R> {
-    fn read_into(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
-        self.0.read_exact(buf)
+    pub(crate) fn options(&self) -> &ReadOptions {
+        &self.options
+    }
+
+    /// Fills ``buf``, running out of data is [``AnimXError::UnexpectedEof``] rather than an io error
+    fn read_into(&mut self, buf: &mut [u8]) -> Result<(), AnimXError> {
+        #[cfg(feature = "std")]
+        self.inner.read_exact(buf).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => AnimXError::UnexpectedEof(buf.len()),
+            _ => e.into(),
+        })?;
+        #[cfg(not(feature = "std"))]
+        self.inner.read_exact(buf)?;
+        self.position += buf.len() as u64;
+        Ok(())
     }
 
-    fn read_bytes(&mut self, len: usize) -> std::io::Result<Vec<u8>> {
+    /// Reads a byte that has to be one of the values of ``T``, ``error`` gets its offset and the byte if it isn't
+    fn read_enum<T: TryFrom<u8>>(&mut self, error: fn(u64, u8) -> AnimXError) -> Result<T, AnimXError> {
+        let byte = self.read_u8()?;
+        T::try_from(byte).map_err(|_| error(self.position - 1, byte))
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, AnimXError> {
+        if len > self.options.max_string_len {
+            return Err(AnimXError::LimitExceeded(len));
+        }
         let mut buf = vec![0u8; len];
-        self.0.read_exact(&mut buf)?;
+        self.read_into(&mut buf)?;
         Ok(buf)
     }
 
-    fn read_bool(&mut self) -> std::io::Result<bool> {
-        let mut buf = [0u8;1];
-        self.0.read_exact(&mut buf)?;
-        Ok(buf[0] == 1)
+    fn read_bool(&mut self) -> Result<bool, AnimXError> {
+        Ok(self.read_u8()? == 1)
     }
 
-    fn read_u8(&mut self) -> std::io::Result<u8> {
+    fn read_u8(&mut self) -> Result<u8, AnimXError> {
         let mut buf = [0u8;1];
-        self.0.read_exact(&mut buf)?;
+        self.read_into(&mut buf)?;
         Ok(buf[0])
     }
 
-    fn read_i32(&mut self) -> std::io::Result<i32> {
+    fn read_i32(&mut self) -> Result<i32, AnimXError> {
         let mut buf = [0u8;4];
-        self.0.read_exact(&mut buf)?;
+        self.read_into(&mut buf)?;
         Ok(i32::from_le_bytes(buf))
     }
 
-    fn read_f32(&mut self) -> std::io::Result<f32> {
+    fn read_f32(&mut self) -> Result<f32, AnimXError> {
         let mut buf = [0u8;4];
-        self.0.read_exact(&mut buf)?;
+        self.read_into(&mut buf)?;
         Ok(f32::from_le_bytes(buf))
     }
 
-    fn read_varint(&mut self) -> std::io::Result<usize> {
+    fn read_varint(&mut self) -> Result<usize, AnimXError> {
         let mut data = 0;
         let mut shift = 0;
-        let mut buf = [0u8;1];
-        while { self.0.read_exact(&mut buf)?; buf[0] & 128 == 128 } {
-            data += (buf[0] as usize & 127) << shift;
+        loop {
+            let byte = self.read_u8()?;
+            let bits = byte as usize & 127;
+            // Bits that would be shifted out mean the stream is corrupt, the number can't be a length
+            if shift >= usize::BITS || bits.leading_zeros() < shift {
+                return Err(AnimXError::VarintOverflow);
+            }
+            data += bits << shift;
+            if byte & 128 == 0 {
+                return Ok(data);
+            }
             shift += 7;
         }
-        data += (buf[0] as usize & 127) << shift;
-
-        Ok(data)
     }
 
     fn read_string(&mut self) -> Result<String, AnimXError> {
@@ -294,11 +1277,57 @@ impl<R: Read> AnimXReader<R> {
 
     fn read_nullable_string(&mut self) -> Result<Option<String>, AnimXError> {
         if self.read_bool()? {
-            self.read_string().map(|s| Some(s))
+            self.read_string().map(Some)
         } else {
             Ok(None)
         }
     }
+
+    /// Node or property of a track, see [``ReadOptions::target_encoding``]
+    fn read_target(&mut self) -> Result<Option<String>, AnimXError> {
+        match self.options.target_encoding {
+            OptStringEncoding::Flagged => self.read_nullable_string(),
+            OptStringEncoding::LengthPrefixed => self.read_string().map(|target| Some(target).filter(|t| !t.is_empty())),
+        }
+    }
+}
+
+/// Length in bytes of the track at the start of ``bytes``, which starts ``position`` bytes into the stream
+#[cfg(any(feature = "rayon", feature = "tokio"))]
+fn track_len(bytes: &[u8], options: &ReadOptions, position: u64) -> Result<usize, AnimXError> {
+    let mut reader = AnimXReader { inner: bytes, options: options.clone(), position };
+    let track_type: TrackType = reader.read_enum(|offset, byte| AnimXError::IncorrectTrackType { offset, byte })?;
+    let value_type: ValueType = reader.read_enum(|offset, byte| AnimXError::IncorrectValueType { offset, byte })?;
+
+    let Some(size) = value_type.size_bytes() else {
+        // Strings don't have a fixed size, the only way to find the end is reading them
+        let mut reader = AnimXReader { inner: bytes, options: options.clone(), position };
+        Animation::read_track(&mut reader)?;
+        return Ok(bytes.len() - reader.inner.len());
+    };
+
+    reader.read_target()?;
+    reader.read_target()?;
+    let frames = reader.read_varint()?;
+
+    let keyframes = match track_type {
+        TrackType::Raw => frames.checked_mul(size).and_then(|len| len.checked_add(4)),
+        TrackType::Discrete => frames.checked_mul(4 + size),
+        TrackType::Curve => {
+            let info = Bool2::read(&mut reader)?;
+            let interpolations = if info.x { frames } else { 1 };
+            let per_keyframe = if info.y { 3 * (4 + size) } else { 4 + size };
+            frames.checked_mul(per_keyframe).and_then(|len| len.checked_add(interpolations))
+        },
+        TrackType::Bezier => frames.checked_mul(4 + 3 * size),
+    };
+
+    let header = bytes.len() - reader.inner.len();
+    match keyframes.and_then(|len| len.checked_add(header)) {
+        Some(len) if len <= bytes.len() => Ok(len),
+        Some(len) => Err(AnimXError::UnexpectedEof(len)),
+        None => Err(AnimXError::VarintOverflow),
+    }
 }
 
 impl<'de> Deserialize<'de> for Animation {
@@ -310,7 +1339,7 @@ impl<'de> Deserialize<'de> for Animation {
         impl<'de> Visitor<'de> for AnimVisitor {
             type Value = Animation;
         
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("a map with a tracks list")
             }
             
@@ -322,26 +1351,36 @@ impl<'de> Deserialize<'de> for Animation {
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
                         "name" => {
-                            let name: String = map.next_value()?;
-                            output.name = Some(name);
+                            output.name = map.next_value()?;
                         },
                         "globalDuration" => {
-                            let f: f32 = map.next_value()?;
-                            output.global_duration = Some(f);
+                            output.global_duration = map.next_value()?;
                         },
                         "tracks" => {
                             let v: serde_json::Value = map.next_value()?;
                             let tracks = v.as_array().ok_or(Error::custom("incorrect field type for \"tracks\", expected 'Value::Array'"))?;
-                            let tracks = tracks.iter().map(|v| {
+                            let tracks = tracks.iter().enumerate().map(|(index, v)| {
+                                // Hand edited files tend to lose these, and serde's "missing field" doesn't say which track it's about
+                                for field in ["trackType", "valueType"] {
+                                    if v.get(field).is_none() {
+                                        Err(serde_json::Error::custom(format!("track {index} is missing the \"{field}\" field")))?
+                                    }
+                                }
                                 let v = v.clone();
                                 let info: TrackInfo = serde_json::from_value(v.clone())?;
 
-                                // This technically makes Curve keyframes on String values possible...
+                                // Usually the keyframe values don't have the shape the valueType says they should
+                                let mismatch = |e: serde_json::Error| serde_json::Error::custom(format!(
+                                    "track {index} doesn't match its declared type ({:?} of {:?}): {e}", info.track_type, info.value_type
+                                ));
+
+                                // This technically makes Curve keyframes on String values possible, Animation::validate catches them
                                 let track = metamatch::metamatch!(match info.track_type {
                                     #[expand(for (T,X) in [
                                         (Raw, RawData),
                                         (Discrete, DiscreteData),
                                         (Curve, CurveData),
+                                        (Bezier, BezierData),
                                     ])]
                                     TrackType::T => {
                                         metamatch::metamatch!(match info.value_type {
@@ -357,10 +1396,9 @@ impl<'de> Deserialize<'de> for Animation {
                                                 DoubleQ, Double2x2, Double3x3, Double4x4,
                                                 Color, Color32, OptString,
                                             ])]
-                                            ValueType::V => serde_json::from_value::<Box<Track<X<V>>>>(v)? as Box<dyn TrackTrait>,
+                                            ValueType::V => serde_json::from_value::<Box<Track<X<V>>>>(v).map_err(mismatch)? as Box<dyn TrackTrait>,
                                         })
                                     },
-                                    TrackType::Bezier => todo!(),
                                 });
                                 Ok(track)
                             }).map(|r| r.map_err(|e: serde_json::Error| Error::custom(e)));
@@ -369,7 +1407,7 @@ impl<'de> Deserialize<'de> for Animation {
                             }
                         },
                         _ => {
-                            let _: IgnoredAny = map.next_value()?;
+                            output.extra.insert(key, map.next_value()?);
                         },
                     }
                 }
@@ -382,162 +1420,1157 @@ impl<'de> Deserialize<'de> for Animation {
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct TrackInfo where {
-    #[serde(rename = "trackType")]
-    pub track_type: TrackType,
-    #[serde(rename = "valueType")]
-    pub value_type: ValueType,
+impl AsRef<[Box<dyn TrackTrait>]> for Animation {
+    fn as_ref(&self) -> &[Box<dyn TrackTrait>] {
+        &self.tracks
+    }
 }
 
-#[allow(private_bounds)]
-#[derive(Debug, Deserialize)]
-pub struct Track<T> where T: KeyframeTrait {
-    #[serde(rename = "trackType")]
-    pub track_type: TrackType,
-    #[serde(rename = "valueType")]
-    pub value_type: ValueType,
-    pub data: T,
+impl Serialize for Animation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        self.serialize_with_tracks(serializer, &self.tracks.iter().map(|t| t.as_ref()).collect::<Vec<_>>(), &AnimJOptions::default())
+    }
 }
 
-impl<T> WriteBytes for Track<T> where T: KeyframeTrait {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        write(&[self.track_type as u8, self.value_type as u8]);
-        self.data.write(write);
+impl Animation {
+    /// Parses an AnimJ (JSON) string, the same as ``serde_json::from_str`` but with a crate error type
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim = Animation::from_animj_str(r#"{"name": "Empty", "tracks": []}"#).unwrap();
+    /// assert_eq!(anim.name.as_deref(), Some("Empty"));
+    ///
+    /// let error = Animation::from_animj_str(r#"{"tracks": [{"valueType": "float"}]}"#).unwrap_err();
+    /// assert!(error.to_string().contains("track 0 is missing the \"trackType\" field"));
+    /// ```
+    pub fn from_animj_str(s: &str) -> Result<Animation, AnimJError> {
+        Ok(serde_json::from_str(s)?)
     }
-}
 
-impl<T> TrackTrait for Track<T> where T: KeyframeTrait {}
+    /// Writes the animation as an AnimJ (JSON) string, the same as ``serde_json::to_string``
+    pub fn to_animj_string(&self) -> Result<String, AnimJError> {
+        self.to_animj_string_with_options(&AnimJOptions::default())
+    }
+
+    /// Same as [``Animation::to_animj_string``], but with [``AnimJOptions``] to control how the values are written
+    pub fn to_animj_string_with_options(&self, options: &AnimJOptions) -> Result<String, AnimJError> {
+        struct WithOptions<'a>(&'a Animation, &'a AnimJOptions);
+        impl Serialize for WithOptions<'_> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+                self.0.serialize_with_tracks(serializer, &self.0.tracks.iter().map(|t| t.as_ref()).collect::<Vec<_>>(), self.1)
+            }
+        }
+
+        Ok(serde_json::to_string(&WithOptions(self, options))?)
+    }
+
+    /// Reads an AnimJ (JSON) stream, see [``Animation::from_animj_str``]\
+    /// The reader is buffered internally, serde_json reads it a byte at a time
+    #[cfg(feature = "std")]
+    pub fn from_animj(data: impl Read) -> Result<Animation, AnimJError> {
+        Ok(serde_json::from_reader(BufReader::new(data))?)
+    }
+
+    /// Writes the animation as AnimJ with the tracks of the same node next to each other, which is nicer for editing by hand
+    ///
+    /// The output is still a regular (pretty printed) AnimJ file, just with the tracks sorted by node.
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "b", "property": "x", "keyframes": []}},
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "a", "property": "x", "keyframes": []}},
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "b", "property": "y", "keyframes": []}}
+    /// ]}"#).unwrap();
+    ///
+    /// let mut buf = Vec::new();
+    /// anim.write_animj_grouped(&mut buf).unwrap();
+    /// let grouped: Animation = serde_json::from_slice(&buf).unwrap();
+    /// let order: Vec<_> = grouped.tracks.iter().map(|t| (t.node().unwrap(), t.property().unwrap())).collect();
+    /// assert_eq!(order, [("a", "x"), ("b", "x"), ("b", "y")]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_animj_grouped(&self, buf: impl Write) -> serde_json::Result<()> {
+        let mut tracks: Vec<_> = self.tracks.iter().map(|t| t.as_ref()).collect();
+        tracks.sort_by(|a, b| a.node().cmp(&b.node()));
+        self.serialize_with_tracks(&mut serde_json::Serializer::pretty(buf), &tracks, &AnimJOptions::default())
+    }
+
+    fn serialize_with_tracks<S>(&self, serializer: S, tracks: &[&dyn TrackTrait], options: &AnimJOptions) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        // Tracks are trait objects, so they're serialized through ``serde_json::Value``s
+        let tracks = tracks.iter()
+            .map(|track| {
+                let mut json = track.to_animj()?;
+                if options.floats == AnimJFloats::Shortest {
+                    shorten_floats(&mut json, track.value_type());
+                }
+                Ok(json)
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>()
+            .map_err(serde::ser::Error::custom)?;
+
+        // Exporters leave out the name & duration when there isn't one, so no nulls here
+        let len = 1 + self.name.is_some() as usize + self.global_duration.is_some() as usize + self.extra.len();
+        let mut map = serializer.serialize_map(Some(len))?;
+        if let Some(name) = &self.name {
+            map.serialize_entry("name", name)?;
+        }
+        if let Some(duration) = &self.global_duration {
+            map.serialize_entry("globalDuration", duration)?;
+        }
+        map.serialize_entry("tracks", &tracks)?;
+        for (key, value) in &self.extra {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Rewrites the ``f32``s of a track written by ``to_animj`` with the fewest digits that read back as the same ``f32``, see [``AnimJFloats::Shortest``]
+///
+/// ``serde_json::Value`` doesn't know which numbers were ``f32``s, so it goes by the value type:
+/// every float of a ``f32`` track is one, tracks of doubles only have their times & interval shortened
+fn shorten_floats(track: &mut serde_json::Value, value_type: ValueType) {
+    fn shorten(json: &mut serde_json::Value) {
+        match json {
+            serde_json::Value::Number(number) if number.is_f64() => {
+                let wide = number.as_f64().unwrap();
+                let narrow = wide as f32;
+                let short: f64 = format!("{narrow}").parse().unwrap();
+                // Nearly always the same ``f32``, if it isn't the wide form is kept so nothing changes
+                if short as f32 == narrow && let Some(short) = serde_json::Number::from_f64(short) {
+                    *number = short;
+                }
+            },
+            serde_json::Value::Array(values) => values.iter_mut().for_each(shorten),
+            serde_json::Value::Object(map) => map.values_mut().for_each(shorten),
+            _ => {},
+        }
+    }
+
+    use ValueType::*;
+    if !matches!(value_type, Double | Double2 | Double3 | Double4 | DoubleQ | Double2x2 | Double3x3 | Double4x4) {
+        return shorten(track);
+    }
+
+    let Some(data) = track.get_mut("data") else { return };
+    if let Some(interval) = data.get_mut("interval") {
+        shorten(interval);
+    }
+    for keyframe in data.get_mut("keyframes").and_then(|k| k.as_array_mut()).into_iter().flatten() {
+        for (key, value) in keyframe.as_object_mut().into_iter().flatten() {
+            match (key.as_str(), value) {
+                ("time", time) => shorten(time),
+                // Tangents are ``[time, value]``
+                ("leftTangent" | "rightTangent", serde_json::Value::Array(tangent)) => tangent.iter_mut().take(1).for_each(shorten),
+                _ => {},
+            }
+        }
+    }
+}
+
+/// The AnimJ of the animation as a JSON value, for editing it in memory without going through a string\
+/// [``serde_json::from_value``] turns it back into an animation
+///
+/// ```
+/// use resonite_core::animation::{Animation, CurveKeyframe, DiscreteKeyframe};
+///
+/// let anim = Animation::builder()
+///     .name("wave")
+///     .global_duration(2.0)
+///     .add_curve_track("Arm", "Rotation", vec![CurveKeyframe::linear(0.0, 0.1f32), CurveKeyframe::hold(1.0, 90.0)])
+///     .add_discrete_track("Arm", "Visible", vec![DiscreteKeyframe { time: 0.5, value: true }])
+///     .add_raw_track("Arm", "Scale", 0.1, vec![1.0f64, 1.1, 1.2])
+///     .finish();
+///
+/// let mut value = serde_json::Value::from(anim.clone());
+/// assert_eq!(value["name"], "wave");
+/// let read: Animation = serde_json::from_value(value.clone()).unwrap();
+/// assert!(read.bit_identical(&anim));
+///
+/// value["name"] = "renamed".into();
+/// let renamed: Animation = serde_json::from_value(value).unwrap();
+/// assert_eq!(renamed.name.as_deref(), Some("renamed"));
+/// ```
+impl From<Animation> for serde_json::Value {
+    fn from(animation: Animation) -> Self {
+        serde_json::to_value(animation).expect("animations are always representable as JSON")
+    }
+}
 
-#[allow(private_bounds)]
 #[derive(Debug, Deserialize)]
-pub struct RawData<T> where T: WriteBytes + Debug {
+struct TrackInfo where {
+    #[serde(rename = "trackType")]
+    pub track_type: TrackType,
+    #[serde(rename = "valueType")]
+    pub value_type: ValueType,
+}
+
+/// A single track of an animation, ``T`` being one of [``RawData``], [``DiscreteData``], [``CurveData``] or [``BezierData``]
+///
+/// This is how a track ends up in an AnimX stream, if this changes the format changed:
+///
+/// ```
+/// use resonite_core::animation::Animation;
+///
+/// let anim: Animation = serde_json::from_str(r#"{"name": "", "globalDuration": 0.0, "tracks": [{"trackType": "Discrete", "valueType": "float",
+///     "data": {"node": "n", "property": "p", "keyframes": [{"time": 0.5, "value": 2.0}]}}]}"#).unwrap();
+///
+/// let mut buf = Vec::new();
+/// anim.write_animx(&mut buf).unwrap();
+/// assert_eq!(buf[17..], [
+///     1, 21,              // Track type (discrete), value type (float)
+///     1, b'n', 1, b'p',   // Node, property
+///     1,                  // Keyframe count
+///     0, 0, 0, 0x3f,      // Time
+///     0, 0, 0, 0x40,      // Value
+/// ]);
+/// ```
+#[allow(private_bounds)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Track<T> where T: KeyframeTrait {
+    #[serde(rename = "trackType")]
+    pub track_type: TrackType,
+    #[serde(rename = "valueType")]
+    pub value_type: ValueType,
+    pub data: T,
+}
+
+impl<T> WriteBytes for Track<T> where T: KeyframeTrait {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        write_track_v1(writer, self.track_type, self.value_type, self.data.node(), self.data.property(), self.data.keyframe_count(), &self.data)
+    }
+}
+
+/// The layout of a track in a version 1 AnimX stream, anything that changes it should happen here
+///
+/// Every track starts with its track type, value type, node, property and keyframe count,
+/// followed by the data specific to the track type, which ``data`` writes:
+/// - Raw: interval, then the values
+/// - Discrete: time & value of every keyframe
+/// - Curve: info byte, interpolation(s), time & value of every keyframe, then the tangents
+/// - Bezier: time, value, left & right control of every keyframe
+fn write_track_v1(writer: &mut AnimXWriter, track_type: TrackType, value_type: ValueType, node: Option<&str>, property: Option<&str>, keyframes: usize, data: &dyn WriteBytes) -> io::Result<()> {
+    writer.write(&[track_type as u8, value_type as u8])?;
+    for target in [node, property] {
+        match (writer.options().target_encoding, target) {
+            (OptStringEncoding::Flagged, None) => writer.write(&[0x00])?,
+            (OptStringEncoding::Flagged, Some(target)) => {
+                writer.write(&[0x01])?;
+                target.write(writer)?;
+            },
+            (OptStringEncoding::LengthPrefixed, target) => target.unwrap_or_default().write(writer)?,
+        }
+    }
+    keyframes.write(writer)?;
+    data.write(writer)
+}
+
+impl<T> TrackTrait for Track<T> where T: KeyframeTrait + Clone + Serialize + 'static {
+    fn node(&self) -> Option<&str> {
+        self.data.node()
+    }
+
+    fn set_node(&mut self, node: Option<String>) {
+        self.data.set_node(node);
+    }
+
+    fn property(&self) -> Option<&str> {
+        self.data.property()
+    }
+
+    fn track_type(&self) -> TrackType {
+        self.track_type
+    }
+
+    fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn TrackTrait> {
+        Box::new(self.clone())
+    }
+
+    fn to_animj(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+
+    fn keyframe_count(&self) -> usize {
+        self.data.keyframe_count()
+    }
+
+    fn value_at(&self, index: usize) -> Option<Value> {
+        Value::from_any(self.value_type, self.data.value_at(index)?)
+    }
+
+    fn keyframe_time(&self, index: usize) -> Option<f32> {
+        self.data.keyframe_time(index)
+    }
+
+    fn keyframe_index_at(&self, time: f32) -> Option<usize> {
+        self.data.keyframe_index_at(time)
+    }
+
+    fn interval(&self) -> Option<f32> {
+        self.data.interval()
+    }
+
+    fn offset_times(&mut self, delta: f32) {
+        self.data.offset_times(delta);
+    }
+
+    fn shift_times(&mut self, delta: f32) {
+        self.data.shift_times(delta);
+    }
+
+    fn scale_times(&mut self, factor: f32) {
+        self.data.scale_times(factor);
+    }
+
+    fn end_time(&self) -> f32 {
+        self.data.end_time()
+    }
+
+    fn start_time(&self) -> f32 {
+        self.data.start_time()
+    }
+
+    fn truncate(&mut self, time: f32) {
+        self.data.truncate(time);
+    }
+
+    fn insert_sampled_keyframe(&mut self, time: f32) {
+        let blend = self.blend();
+        self.data.insert_sampled_keyframe(time, blend);
+    }
+
+    fn is_sorted(&self) -> bool {
+        self.data.is_sorted()
+    }
+
+    fn sort_keyframes(&mut self) {
+        self.data.sort_keyframes();
+    }
+
+    fn scale_values(&mut self, factor: f32) -> bool {
+        if self.value_type.is_quaternion() {
+            return false;
+        }
+        self.data.scale_values(factor)
+    }
+
+    fn nonfinite_keyframes(&self) -> Vec<usize> {
+        self.data.nonfinite_keyframes()
+    }
+
+    fn validate(&self, index: usize, errors: &mut Vec<ValidationError>) {
+        self.data.validate(index, errors);
+    }
+
+    fn merge_keyframes(&mut self, other: &dyn TrackTrait) -> bool {
+        match other.as_any().downcast_ref::<Self>() {
+            // Quaternions are the same type as 4 component vectors, the value type tells them apart
+            Some(other) if self.value_type == other.value_type => self.data.merge(&other.data),
+            _ => false,
+        }
+    }
+
+    fn append_frames(&mut self, other: &dyn TrackTrait, start: f32) -> bool {
+        match other.as_any().downcast_ref::<Self>() {
+            Some(other) if self.value_type == other.value_type => self.data.append(&other.data, start),
+            _ => false,
+        }
+    }
+
+    fn approx_eq(&self, other: &dyn TrackTrait, epsilon: f32) -> bool {
+        match other.as_any().downcast_ref::<Self>() {
+            // Quaternions are the same type as 4 component vectors, the value type tells them apart
+            Some(other) => self.value_type == other.value_type
+                && (self.node(), self.property()) == (other.node(), other.property())
+                && self.data.approx_eq(&other.data, epsilon),
+            None => false,
+        }
+    }
+
+    fn sample_any(&self, time: f32) -> Option<Box<dyn core::any::Any>> {
+        self.sample_blended(time).map(|value| Box::new(value) as Box<dyn core::any::Any>)
+    }
+
+    fn sample_to_discrete(&self, times: &[f32]) -> Box<dyn TrackTrait> {
+        let keyframes = times.iter().filter_map(|&time| {
+            let sampled = self.sample_blended(time)?;
+            // Right on a keyframe its value is taken as is, blending can be a bit off there (raw frames are found by dividing by the interval)
+            let value = match self.data.keyframe_index_at(time) {
+                Some(index) if self.data.keyframe_time(index) == Some(time) => self.data.value_at(index)?.clone(),
+                _ => sampled,
+            };
+            Some(DiscreteKeyframe { time, value })
+        }).collect();
+        Box::new(Track {
+            track_type: TrackType::Discrete,
+            value_type: self.value_type,
+            data: DiscreteData {
+                node: self.data.node().map(str::to_owned),
+                property: self.data.property().map(str::to_owned),
+                keyframes,
+            },
+        })
+    }
+
+    fn decimate(&mut self, tolerance: f32) -> usize {
+        let blend = self.blend();
+        self.data.decimate(tolerance, blend)
+    }
+
+    fn trim(&self, start: f32, end: f32) -> Box<dyn TrackTrait> {
+        let mut track = self.clone();
+        track.data.trim(start, end, self.blend());
+        Box::new(track)
+    }
+
+    fn sample_to_raw(&self, interval: f32, frames: usize) -> Option<Box<dyn TrackTrait>> {
+        if !self.value_type.is_interpolatable() {
+            return None;
+        }
+
+        let keyframes = (0..frames).map_while(|frame| self.sample_blended(frame as f32 * interval)).collect();
+        Some(Box::new(Track {
+            track_type: TrackType::Raw,
+            value_type: self.value_type,
+            data: RawData {
+                node: self.data.node().map(str::to_owned),
+                property: self.data.property().map(str::to_owned),
+                interval: Some(interval),
+                keyframes,
+            },
+        }))
+    }
+
+    fn convert_value_type(&self, value_type: ValueType) -> Option<Box<dyn TrackTrait>> {
+        convert::convert_value_type(self, value_type)
+    }
+}
+
+#[allow(private_bounds)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RawData<T> where T: ValueTrait {
     pub node: Option<String>,
     pub property: Option<String>,
     pub interval: Option<f32>,
     pub keyframes: Vec<T>,
 }
 
-impl<T> WriteBytes for RawData<T> where T: WriteBytes + Debug {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        self.node.write(write);
-        self.property.write(write);
-        self.keyframes.len().write(write);
-        self.interval.write(write);
+#[allow(private_bounds)]
+impl<T> Track<RawData<T>> where T: ValueTrait {
+    /// Always ``true``, the frames of a raw track are evenly spaced by ``interval``\
+    /// Timing that isn't uniform needs a discrete or curve track instead, which is what merging raw tracks with different intervals results in
+    pub fn raw_is_uniform(&self) -> bool {
+        true
+    }
+}
+
+impl<T> WriteBytes for RawData<T> where T: ValueTrait {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        self.interval.write(writer)?;
         for keyframe in &self.keyframes {
-            keyframe.write(write);
+            keyframe.write(writer)?;
         }
+        Ok(())
     }
 }
 
-impl<T> KeyframeTrait for RawData<T> where T: WriteBytes + Debug {}
+/// Adds ``other`` to ``keyframes`` sorted by time, the keyframes already in ``keyframes`` win where both have one at the same time
+fn merge_by_time<K>(keyframes: &mut Vec<K>, other: &[K], time: impl Fn(&K) -> f32) where K: Clone {
+    // The sort is stable, so the keyframes already there come first and win the dedup
+    keyframes.extend(other.iter().cloned());
+    keyframes.sort_by(|a, b| time(a).total_cmp(&time(b)));
+    keyframes.dedup_by(|later, earlier| time(later) == time(earlier));
+}
+
+/// Most frames that are added in front of (or in between) raw tracks to move them, about 3 hours at 90 fps
+const MAX_SHIFT_FRAMES: usize = 1 << 20;
+
+impl<T> KeyframeTrait for RawData<T> where T: ValueTrait {
+    fn node(&self) -> Option<&str> {
+        self.node.as_deref()
+    }
+
+    fn set_node(&mut self, node: Option<String>) {
+        self.node = node;
+    }
+
+    fn property(&self) -> Option<&str> {
+        self.property.as_deref()
+    }
+
+    fn keyframe_count(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    fn value_at(&self, index: usize) -> Option<&T> {
+        self.keyframes.get(index)
+    }
+
+    fn keyframe_time(&self, index: usize) -> Option<f32> {
+        (index < self.keyframes.len()).then(|| self.interval.unwrap_or_default() * index as f32)
+    }
+
+    fn interval(&self) -> Option<f32> {
+        self.interval
+    }
+
+    fn offset_times(&mut self, _delta: f32) {}
+
+    fn shift_times(&mut self, delta: f32) {
+        let (Some(interval), Some(first)) = (self.interval.filter(|i| *i > 0.0), self.keyframes.first().cloned()) else { return };
+        if delta > 0.0 {
+            let frames = ((delta / interval).round() as usize).min(MAX_SHIFT_FRAMES);
+            self.keyframes.splice(0..0, core::iter::repeat_n(first, frames));
+        }
+    }
+
+    fn append(&mut self, other: &Self, start: f32) -> bool {
+        let Some(interval) = self.interval.filter(|i| *i > 0.0 && other.interval == Some(*i)) else { return false };
+        let Some(hold) = self.keyframes.last().or(other.keyframes.first()).cloned() else { return true };
+
+        // The last frame stays until ``other`` starts, which is never before the end of this track
+        let frames = ((start / interval).round() as usize).min(self.keyframes.len() + MAX_SHIFT_FRAMES);
+        let held = frames.saturating_sub(self.keyframes.len());
+        self.keyframes.extend(core::iter::repeat_n(hold, held));
+        self.keyframes.extend(other.keyframes.iter().cloned());
+        true
+    }
+
+    fn scale_times(&mut self, factor: f32) {
+        self.interval = self.interval.map(|i| i * factor);
+    }
+
+    fn end_time(&self) -> f32 {
+        self.interval.unwrap_or_default() * self.keyframes.len().saturating_sub(1) as f32
+    }
+
+    fn truncate(&mut self, time: f32) {
+        let interval = self.interval.unwrap_or_default();
+        if interval > 0.0 && time >= 0.0 {
+            self.keyframes.truncate((time / interval).floor() as usize + 1);
+        } else if time < 0.0 {
+            self.keyframes.clear();
+        }
+    }
+
+    fn is_sorted(&self) -> bool {
+        true
+    }
+
+    fn sort_keyframes(&mut self) {}
+
+    fn scale_values(&mut self, factor: f32) -> bool {
+        self.keyframes.iter_mut().all(|value| value.scale(factor))
+    }
+
+    fn nonfinite_keyframes(&self) -> Vec<usize> {
+        (0..self.keyframes.len()).filter(|&i| !self.keyframes[i].is_finite()).collect()
+    }
+
+    fn trim(&mut self, start: f32, end: f32, blend: Blend<T>) {
+        RawData::trim(self, start, end, blend);
+    }
+
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        RawData::approx_eq(self, other, epsilon)
+    }
+
+    type Value = T;
+    fn sample_with(&self, time: f32, blend: Blend<T>) -> Option<T> {
+        RawData::sample_with(self, time, blend)
+    }
+}
 
 #[allow(private_bounds)]
-#[derive(Debug, Deserialize)]
-pub struct DiscreteData<T> where T: WriteBytes + Debug {
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiscreteData<T> where T: ValueTrait {
     pub node: Option<String>,
     pub property: Option<String>,
     pub keyframes: Vec<DiscreteKeyframe<T>>,
 }
 
-impl<T> WriteBytes for DiscreteData<T> where T: WriteBytes + Debug {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        self.node.write(write);
-        self.property.write(write);
-        self.keyframes.len().write(write);
+impl<T> WriteBytes for DiscreteData<T> where T: ValueTrait {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
         for keyframe in &self.keyframes {
-            keyframe.write(write);
+            keyframe.write(writer)?;
         }
+        Ok(())
     }
 }
 
-impl<T> KeyframeTrait for DiscreteData<T> where T: WriteBytes + Debug {}
+impl<T> KeyframeTrait for DiscreteData<T> where T: ValueTrait {
+    fn node(&self) -> Option<&str> {
+        self.node.as_deref()
+    }
+
+    fn set_node(&mut self, node: Option<String>) {
+        self.node = node;
+    }
+
+    fn property(&self) -> Option<&str> {
+        self.property.as_deref()
+    }
+
+    fn keyframe_count(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    fn value_at(&self, index: usize) -> Option<&T> {
+        self.keyframes.get(index).map(|k| &k.value)
+    }
+
+    fn keyframe_time(&self, index: usize) -> Option<f32> {
+        self.keyframes.get(index).map(|k| k.time)
+    }
+
+    fn offset_times(&mut self, delta: f32) {
+        self.keyframes.iter_mut().for_each(|k| k.time += delta);
+    }
+
+    fn scale_times(&mut self, factor: f32) {
+        self.keyframes.iter_mut().for_each(|k| k.time *= factor);
+    }
+
+    fn end_time(&self) -> f32 {
+        self.keyframes.iter().map(|k| k.time).fold(0.0, f32::max)
+    }
+
+    fn start_time(&self) -> f32 {
+        self.keyframes.iter().map(|k| k.time).reduce(f32::min).unwrap_or_default()
+    }
+
+    fn truncate(&mut self, time: f32) {
+        self.keyframes.retain(|k| k.time <= time);
+    }
+
+    fn insert_sampled_keyframe(&mut self, time: f32, _blend: Blend<T>) {
+        DiscreteData::insert_sampled_keyframe(self, time);
+    }
+
+    fn is_sorted(&self) -> bool {
+        self.keyframes.is_sorted_by(|a, b| a.time <= b.time)
+    }
+
+    fn sort_keyframes(&mut self) {
+        self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    fn scale_values(&mut self, factor: f32) -> bool {
+        self.keyframes.iter_mut().all(|k| k.value.scale(factor))
+    }
+
+    fn nonfinite_keyframes(&self) -> Vec<usize> {
+        (0..self.keyframes.len()).filter(|&i| {
+            let k = &self.keyframes[i];
+            !k.time.is_finite() || !k.value.is_finite()
+        }).collect()
+    }
+
+    fn validate(&self, track: usize, errors: &mut Vec<ValidationError>) {
+        validate::validate_times(track, self.keyframes.iter().map(|k| k.time), errors);
+    }
+
+    fn merge(&mut self, other: &Self) -> bool {
+        merge_by_time(&mut self.keyframes, &other.keyframes, |k| k.time);
+        true
+    }
+
+    fn decimate(&mut self, tolerance: f32, _blend: Blend<T>) -> usize {
+        DiscreteData::decimate(self, tolerance)
+    }
+
+    fn trim(&mut self, start: f32, end: f32, _blend: Blend<T>) {
+        DiscreteData::trim(self, start, end);
+    }
+
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        DiscreteData::approx_eq(self, other, epsilon)
+    }
+
+    type Value = T;
+    fn sample_with(&self, time: f32, _blend: Blend<T>) -> Option<T> {
+        DiscreteData::sample(self, time)
+    }
+}
 
 #[allow(private_bounds)]
-#[derive(Debug, Deserialize)]
-pub struct DiscreteKeyframe<T> where T: WriteBytes + Debug {
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiscreteKeyframe<T> where T: ValueTrait {
     pub time: f32,
     pub value: T,
 }
 
-impl<T> WriteBytes for DiscreteKeyframe<T> where T: WriteBytes + Debug {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        self.time.write(write);
-        self.value.write(write);
+/// Makes ``(time, value)`` pairs usable as keyframes
+///
+/// ```
+/// use resonite_core::animation::{DiscreteData, types::Float};
+///
+/// let data: DiscreteData<Float> = DiscreteData {
+///     node: Some("n".to_owned()),
+///     property: Some("p".to_owned()),
+///     keyframes: vec![(0.0, 1.0).into(), (0.5, 2.0).into()],
+/// };
+/// assert_eq!(data.keyframes[1].time, 0.5);
+/// assert_eq!(data.keyframes[1].value, 2.0);
+/// ```
+#[allow(private_bounds)]
+impl<T> From<(f32, T)> for DiscreteKeyframe<T> where T: ValueTrait {
+    fn from((time, value): (f32, T)) -> Self {
+        Self { time, value }
+    }
+}
+
+impl<T> WriteBytes for DiscreteKeyframe<T> where T: ValueTrait {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        self.time.write(writer)?;
+        self.value.write(writer)
     }
 }
 
+/// Keyframes with their own interpolation, and tangents for the ``Tangent`` & ``CubicBezier`` modes
+///
+/// In AnimX the tangents come after all of the values, left then right for every keyframe, each one a time followed by a value:
+///
+/// ```
+/// use resonite_core::animation::{Animation, CurveData, Track, types::Float};
+///
+/// let anim: Animation = serde_json::from_str(r#"{"tracks": [{"trackType": "Curve", "valueType": "float",
+///     "data": {"node": "n", "property": "p", "keyframes": [
+///         {"time": 0.0, "value": 0.0, "interpolation": "Tangent", "leftTangent": [-0.5, 1.0], "rightTangent": [0.5, 2.0]},
+///         {"time": 1.0, "value": 1.0, "interpolation": "Tangent", "leftTangent": [-0.5, 3.0], "rightTangent": [0.5, 4.0]},
+///         {"time": 2.0, "value": 0.0, "interpolation": "Tangent", "leftTangent": [-0.5, 5.0], "rightTangent": [0.5, 6.0]},
+///         {"time": 3.0, "value": 1.0, "interpolation": "Tangent", "leftTangent": [-0.5, 7.0], "rightTangent": [0.5, 8.0]}
+///     ]}}]}"#).unwrap();
+///
+/// let mut buf = Vec::new();
+/// anim.write_animx(&mut buf).unwrap();
+/// let read = Animation::from_animx(&buf[..]).unwrap();
+/// let curve = read.tracks[0].as_any().downcast_ref::<Track<CurveData<Float>>>().unwrap();
+/// for (i, keyframe) in curve.data.keyframes.iter().enumerate() {
+///     assert_eq!(keyframe.left_tangent, Some((-0.5, i as f32 * 2.0 + 1.0)));
+///     assert_eq!(keyframe.right_tangent, Some((0.5, i as f32 * 2.0 + 2.0)));
+/// }
+/// ```
+///
+/// They start with an info byte, bit 0 says every keyframe has its own interpolation (otherwise there's only one for all of them)
+/// and bit 1 says there are tangents:
+///
+/// ```
+/// use resonite_core::animation::Animation;
+///
+/// let curve = |first: &str, second: &str| {
+///     let anim: Animation = serde_json::from_str(&format!(r#"{{"name": "", "globalDuration": 0.0, "tracks": [{{"trackType": "Curve", "valueType": "float",
+///         "data": {{"node": "n", "property": "p", "keyframes": [
+///             {{"time": 0.0, "value": 0.0, "interpolation": "{first}"}},
+///             {{"time": 1.0, "value": 1.0, "interpolation": "{second}"}}
+///         ]}}}}]}}"#)).unwrap();
+///     let buf = anim.to_animx_bytes().unwrap();
+///     assert_eq!(Animation::from_animx(&buf[..]).unwrap().to_animx_bytes().unwrap(), buf);
+///     buf
+/// };
+///
+/// let uniform = curve("Linear", "Linear");
+/// assert_eq!(uniform[24..27], [0, 1, 0]); // Info byte, then a single interpolation and the first keyframe's time
+/// let mixed = curve("Hold", "Linear");
+/// assert_eq!(mixed[24..27], [1, 0, 1]); // Info byte, then an interpolation per keyframe
+/// ```
 #[allow(private_bounds)]
-#[derive(Debug, Deserialize)]
-pub struct CurveData<T> where T: WriteBytes + Debug {
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CurveData<T> where T: ValueTrait {
     pub node: Option<String>,
     pub property: Option<String>,
     pub keyframes: Vec<CurveKeyframe<T>>,
 }
 
-impl<T> WriteBytes for CurveData<T> where T: WriteBytes + Debug {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
+impl<T> WriteBytes for CurveData<T> where T: ValueTrait {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
         let interpolation = self.keyframes.first().map(|k| k.interpolation).unwrap_or(Interpolation::Hold);
-        let mut info = 0x1;
-        for keyframe in &self.keyframes {
-            if keyframe.interpolation != interpolation {
-                info |= 0x1;
-            }
-            info |= keyframe.interpolation as u8 & 0x2;
-        }
+        let varies = self.keyframes.iter().any(|k| k.interpolation != interpolation);
+        // Bools and strings can't have tangents, from_animx refuses to read them
+        let tangents = ValueType::of::<T>().is_interpolatable() && self.keyframes.iter().any(|k| k.left_tangent.is_some() || k.right_tangent.is_some());
+        let info = varies as u8 | (tangents as u8) << 1;
 
-        self.node.write(write);
-        self.property.write(write);
-        self.keyframes.len().write(write);
-        write(&[info]);
+        writer.write(&[info])?;
 
-        if info & 0x1 == 0x1 {
+        if varies {
             for keyframe in &self.keyframes {
-                (keyframe.interpolation as u8).write(write);
+                (keyframe.interpolation as u8).write(writer)?;
             }
         } else {
-            (interpolation as u8).write(write);
+            (interpolation as u8).write(writer)?;
         }
 
         for keyframe in &self.keyframes {
-            keyframe.write(write);
+            keyframe.write(writer)?;
         }
 
-        if info & 0x2 == 0x2 {
+        // Same layout from_animx reads: left & right tangent for every keyframe, after all of the values
+        // Once any keyframe has a tangent every keyframe needs both, missing ones are written flat (0, value - value)
+        if tangents {
             for keyframe in &self.keyframes {
-                keyframe.left_tangent.as_ref().expect("interpolation mode was tangent or bezier, but leftTangent wasn't present").write(write);
-                keyframe.right_tangent.as_ref().expect("interpolation mode was tangent or bezier, but rightTangent wasn't present").write(write);
+                let flat = || (0.0, keyframe.value.add_scaled(&keyframe.value, -1.0));
+                for (time, value) in [&keyframe.left_tangent, &keyframe.right_tangent].map(|t| t.clone().unwrap_or_else(flat)) {
+                    time.write(writer)?;
+                    value.write(writer)?;
+                }
             }
         }
+        Ok(())
     }
 }
 
-impl<T> KeyframeTrait for CurveData<T> where T: WriteBytes + Debug {}
+impl<T> KeyframeTrait for CurveData<T> where T: ValueTrait {
+    fn node(&self) -> Option<&str> {
+        self.node.as_deref()
+    }
+
+    fn set_node(&mut self, node: Option<String>) {
+        self.node = node;
+    }
+
+    fn property(&self) -> Option<&str> {
+        self.property.as_deref()
+    }
+
+    fn keyframe_count(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    fn value_at(&self, index: usize) -> Option<&T> {
+        self.keyframes.get(index).map(|k| &k.value)
+    }
+
+    fn keyframe_time(&self, index: usize) -> Option<f32> {
+        self.keyframes.get(index).map(|k| k.time)
+    }
+
+    fn offset_times(&mut self, delta: f32) {
+        self.keyframes.iter_mut().for_each(|k| k.time += delta);
+    }
+
+    fn scale_times(&mut self, factor: f32) {
+        for k in &mut self.keyframes {
+            k.time *= factor;
+            // Tangents are handles with their own time, stretching it flattens them
+            if let Some((time, _)) = &mut k.left_tangent { *time *= factor; }
+            if let Some((time, _)) = &mut k.right_tangent { *time *= factor; }
+        }
+    }
+
+    fn end_time(&self) -> f32 {
+        self.keyframes.iter().map(|k| k.time).fold(0.0, f32::max)
+    }
+
+    fn start_time(&self) -> f32 {
+        self.keyframes.iter().map(|k| k.time).reduce(f32::min).unwrap_or_default()
+    }
+
+    fn truncate(&mut self, time: f32) {
+        self.keyframes.retain(|k| k.time <= time);
+    }
+
+    fn insert_sampled_keyframe(&mut self, time: f32, blend: Blend<T>) {
+        CurveData::insert_sampled_keyframe(self, time, blend);
+    }
+
+    fn is_sorted(&self) -> bool {
+        self.keyframes.is_sorted_by(|a, b| a.time <= b.time)
+    }
+
+    fn sort_keyframes(&mut self) {
+        self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    fn scale_values(&mut self, factor: f32) -> bool {
+        self.keyframes.iter_mut().all(|k| {
+            // Tangent values are in the same units as the values, so they get scaled too
+            if let Some((_, tangent)) = &mut k.left_tangent { tangent.scale(factor); }
+            if let Some((_, tangent)) = &mut k.right_tangent { tangent.scale(factor); }
+            k.value.scale(factor)
+        })
+    }
+
+    fn nonfinite_keyframes(&self) -> Vec<usize> {
+        (0..self.keyframes.len()).filter(|&i| {
+            let k = &self.keyframes[i];
+            !k.time.is_finite() || !k.value.is_finite()
+                || k.left_tangent.as_ref().is_some_and(|(time, t)| !time.is_finite() || !t.is_finite())
+                || k.right_tangent.as_ref().is_some_and(|(time, t)| !time.is_finite() || !t.is_finite())
+        }).collect()
+    }
+
+    fn validate(&self, track: usize, errors: &mut Vec<ValidationError>) {
+        validate::validate_times(track, self.keyframes.iter().map(|k| k.time), errors);
+        let interpolatable = ValueType::of::<T>().is_interpolatable();
+        for (keyframe, k) in self.keyframes.iter().enumerate() {
+            if !interpolatable {
+                if k.interpolation != Interpolation::Hold || k.left_tangent.is_some() || k.right_tangent.is_some() {
+                    errors.push(ValidationError::UninterpolatableCurve { track, keyframe });
+                }
+                continue;
+            }
+            let needs_tangents = matches!(k.interpolation, Interpolation::Tangent | Interpolation::CubicBezier);
+            if needs_tangents && (k.left_tangent.is_none() || k.right_tangent.is_none()) {
+                errors.push(ValidationError::MissingTangents { track, keyframe });
+            }
+        }
+    }
+
+    fn merge(&mut self, other: &Self) -> bool {
+        merge_by_time(&mut self.keyframes, &other.keyframes, |k| k.time);
+        true
+    }
+
+    fn decimate(&mut self, tolerance: f32, blend: Blend<T>) -> usize {
+        CurveData::decimate(self, tolerance, blend)
+    }
+
+    fn trim(&mut self, start: f32, end: f32, blend: Blend<T>) {
+        CurveData::trim(self, start, end, blend);
+    }
+
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        CurveData::approx_eq(self, other, epsilon)
+    }
+
+    type Value = T;
+    fn sample_with(&self, time: f32, blend: Blend<T>) -> Option<T> {
+        CurveData::sample_with(self, time, blend)
+    }
+}
 
 #[allow(private_bounds)]
-#[derive(Debug, Deserialize)]
-pub struct CurveKeyframe<T> where T: WriteBytes + Debug {
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CurveKeyframe<T> where T: ValueTrait {
     pub time: f32,
     pub value: T,
     pub interpolation: Interpolation,
 
-    /// I think the types for ``left_tangent`` & ``right_tangent`` are incorrect but I'm not sure what they should be...\
-    /// Maybe they're supposed to be ``(f32, T)`` pairs?
+    /// Tangents are ``(time, value)`` handles relative to the keyframe, their slope is ``value / time``\
+    /// The left one normally has a negative time, a time of zero counts as flat
 
     #[serde(rename = "leftTangent")]
-    pub left_tangent: Option<T>,
+    pub left_tangent: Option<(f32, T)>,
     #[serde(rename = "rightTangent")]
-    pub right_tangent: Option<T>,
+    pub right_tangent: Option<(f32, T)>,
+}
+
+#[allow(private_bounds)]
+impl<T> CurveKeyframe<T> where T: ValueTrait {
+    /// Keyframe lerping towards the next keyframe
+    pub fn linear(time: f32, value: T) -> Self {
+        Self { time, value, interpolation: Interpolation::Linear, left_tangent: None, right_tangent: None }
+    }
+
+    /// Keyframe keeping its value until the next keyframe
+    pub fn hold(time: f32, value: T) -> Self {
+        Self { time, value, interpolation: Interpolation::Hold, left_tangent: None, right_tangent: None }
+    }
+}
+
+impl<T> WriteBytes for CurveKeyframe<T> where T: ValueTrait {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        self.time.write(writer)?;
+        self.value.write(writer)
+    }
+}
+
+/// Keyframes connected by cubic bezier segments, see [``CurveData::to_bezier``] and [``BezierData::to_curve``]
+///
+/// Unlike curves every keyframe carries both of its control points, there are no interpolation modes or info byte:
+///
+/// ```
+/// use resonite_core::animation::{Animation, BezierData, BezierKeyframe, Track, types::{Float, TrackType, ValueType}};
+///
+/// let track = Track { track_type: TrackType::Bezier, value_type: ValueType::Float, data: BezierData::<Float> {
+///     node: Some("n".into()), property: Some("p".into()), keyframes: vec![
+///         BezierKeyframe { time: 0.0, value: 0.0, left_control: 0.0, right_control: 0.5 },
+///         BezierKeyframe { time: 1.0, value: 2.0, left_control: 1.5, right_control: 2.0 },
+///     ],
+/// }};
+/// let anim = Animation::builder().name("").global_duration(1.0).track(track).finish();
+///
+/// let buf = anim.to_animx_bytes().unwrap();
+/// assert_eq!(buf[17..], [
+///     3, 21, 1, b'n', 1, b'p', 2,
+///     0, 0, 0, 0,  0, 0, 0, 0,  0, 0, 0, 0,  0, 0, 0, 0x3f,
+///     0, 0, 0x80, 0x3f,  0, 0, 0, 0x40,  0, 0, 0xc0, 0x3f,  0, 0, 0, 0x40,
+/// ]);
+///
+/// let read = Animation::from_animx(&buf[..]).unwrap();
+/// let track = read.tracks[0].as_any().downcast_ref::<Track<BezierData<Float>>>().unwrap();
+/// assert_eq!(track.data.keyframes[1].left_control, 1.5);
+/// assert_eq!(read.to_animx_bytes().unwrap(), buf);
+/// ```
+#[allow(private_bounds)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BezierData<T> where T: ValueTrait {
+    pub node: Option<String>,
+    pub property: Option<String>,
+    pub keyframes: Vec<BezierKeyframe<T>>,
+}
+
+impl<T> WriteBytes for BezierData<T> where T: ValueTrait {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        for keyframe in &self.keyframes {
+            keyframe.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> KeyframeTrait for BezierData<T> where T: ValueTrait {
+    fn node(&self) -> Option<&str> {
+        self.node.as_deref()
+    }
+
+    fn set_node(&mut self, node: Option<String>) {
+        self.node = node;
+    }
+
+    fn property(&self) -> Option<&str> {
+        self.property.as_deref()
+    }
+
+    fn keyframe_count(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    fn value_at(&self, index: usize) -> Option<&T> {
+        self.keyframes.get(index).map(|k| &k.value)
+    }
+
+    fn keyframe_time(&self, index: usize) -> Option<f32> {
+        self.keyframes.get(index).map(|k| k.time)
+    }
+
+    fn offset_times(&mut self, delta: f32) {
+        self.keyframes.iter_mut().for_each(|k| k.time += delta);
+    }
+
+    fn scale_times(&mut self, factor: f32) {
+        // Control points are values placed relative to the neighbouring keyframes, so stretching time keeps the shape as is
+        self.keyframes.iter_mut().for_each(|k| k.time *= factor);
+    }
+
+    fn end_time(&self) -> f32 {
+        self.keyframes.iter().map(|k| k.time).fold(0.0, f32::max)
+    }
+
+    fn start_time(&self) -> f32 {
+        self.keyframes.iter().map(|k| k.time).reduce(f32::min).unwrap_or_default()
+    }
+
+    fn truncate(&mut self, time: f32) {
+        self.keyframes.retain(|k| k.time <= time);
+    }
+
+    fn insert_sampled_keyframe(&mut self, time: f32, blend: Blend<T>) {
+        BezierData::insert_sampled_keyframe(self, time, blend);
+    }
+
+    fn is_sorted(&self) -> bool {
+        self.keyframes.is_sorted_by(|a, b| a.time <= b.time)
+    }
+
+    fn sort_keyframes(&mut self) {
+        self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    fn scale_values(&mut self, factor: f32) -> bool {
+        self.keyframes.iter_mut().all(|k| {
+            k.left_control.scale(factor);
+            k.right_control.scale(factor);
+            k.value.scale(factor)
+        })
+    }
+
+    fn nonfinite_keyframes(&self) -> Vec<usize> {
+        (0..self.keyframes.len()).filter(|&i| {
+            let k = &self.keyframes[i];
+            !k.time.is_finite() || !k.value.is_finite() || !k.left_control.is_finite() || !k.right_control.is_finite()
+        }).collect()
+    }
+
+    fn validate(&self, track: usize, errors: &mut Vec<ValidationError>) {
+        if !ValueType::of::<T>().is_interpolatable() && !self.keyframes.is_empty() {
+            errors.push(ValidationError::UninterpolatableBezier { track });
+        }
+        validate::validate_times(track, self.keyframes.iter().map(|k| k.time), errors);
+    }
+
+    fn merge(&mut self, other: &Self) -> bool {
+        merge_by_time(&mut self.keyframes, &other.keyframes, |k| k.time);
+        true
+    }
+
+    fn trim(&mut self, start: f32, end: f32, blend: Blend<T>) {
+        BezierData::trim(self, start, end, blend);
+    }
+
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        BezierData::approx_eq(self, other, epsilon)
+    }
+
+    type Value = T;
+    fn sample_with(&self, time: f32, blend: Blend<T>) -> Option<T> {
+        BezierData::sample_with(self, time, blend)
+    }
+}
+
+/// A keyframe with the control points on either side of it
+///
+/// The control points sit a third of the way towards the previous (``left_control``) and next (``right_control``) keyframe in time,
+/// so only their values are stored
+///
+/// ```
+/// use resonite_core::animation::Animation;
+///
+/// let anim: Animation = serde_json::from_str(r#"{"name": "Bounce", "globalDuration": 1.0, "tracks": [{"trackType": "Bezier", "valueType": "float2",
+///     "data": {"node": "Ball", "property": "Offset", "keyframes": [
+///         {"time": 0.0, "value": {"x": 0.0, "y": 0.0}, "leftControl": {"x": 0.0, "y": 0.0}, "rightControl": {"x": 0.25, "y": 1.0}},
+///         {"time": 1.0, "value": {"x": 1.0, "y": 0.0}, "leftControl": {"x": 0.75, "y": 1.0}, "rightControl": {"x": 1.0, "y": 0.0}}
+///     ]}}]}"#).unwrap();
+///
+/// let read = Animation::from_animx(&anim.to_animx_bytes().unwrap()[..]).unwrap();
+/// assert_eq!(serde_json::to_value(&read).unwrap(), serde_json::to_value(&anim).unwrap());
+/// ```
+#[allow(private_bounds)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BezierKeyframe<T> where T: ValueTrait {
+    pub time: f32,
+    pub value: T,
+    #[serde(rename = "leftControl")]
+    pub left_control: T,
+    #[serde(rename = "rightControl")]
+    pub right_control: T,
 }
 
-impl<T> WriteBytes for CurveKeyframe<T> where T: WriteBytes + Debug {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        self.time.write(write);
-        self.value.write(write);
+impl<T> WriteBytes for BezierKeyframe<T> where T: ValueTrait {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        self.time.write(writer)?;
+        self.value.write(writer)?;
+        self.left_control.write(writer)?;
+        self.right_control.write(writer)
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy)]
 pub enum Interpolation {
     Hold,
     Linear,
@@ -0,0 +1,72 @@
+//! Decoding the tracks of an AnimX stream on multiple threads, only available with the ``rayon`` feature
+
+use std::{borrow::Cow, io::Read};
+
+use rayon::prelude::*;
+
+use super::{AnimXEncoding, AnimXError, AnimXHeader, AnimXReader, Animation, PREALLOCATION_LIMIT, ReadOptions, track_len};
+
+impl Animation {
+    /// Same as [``Animation::from_animx_slice``], but the tracks are decoded on rayon's thread pool
+    ///
+    /// The tracks are scanned first to find where each of them starts, which only reads the track headers
+    /// unless the values are strings, those have to be read in full to find their end.
+    /// Compressed streams are decompressed up front.
+    ///
+    /// Each track costs a bit extra to hand to the thread pool, so files with lots of tiny tracks are faster to read serially.
+    /// Measured with ``cargo run --release --example parallel --features rayon -- <tracks> <keyframes>`` on a single core,
+    /// 2000 tracks of 10 keyframes took twice as long as [``Animation::from_animx_slice``], the two were even around 20 keyframes per track,
+    /// and 512 tracks of 4096 keyframes took half as long, since plain streams are decoded straight from the slice.
+    /// More cores move the crossover further down.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, ReadOptions};
+    ///
+    /// let mut builder = Animation::builder();
+    /// for i in 0..64 {
+    ///     builder = builder.add_raw_track(&format!("Node{i}"), "Value", 0.1, vec![i as f32; 100]);
+    /// }
+    /// let buf = builder.finish().to_animx_bytes().unwrap();
+    ///
+    /// let parallel = Animation::from_animx_parallel(&buf, &ReadOptions::default()).unwrap();
+    /// assert!(parallel.bit_identical(&Animation::from_animx_slice(&buf).unwrap()));
+    /// ```
+    pub fn from_animx_parallel(data: &[u8], options: &ReadOptions) -> Result<Animation, AnimXError> {
+        let mut reader = AnimXReader { inner: data, options: options.clone(), position: 0 };
+        let header = AnimXHeader::read(&mut reader)?;
+        let tracks = match header.encoding {
+            AnimXEncoding::Plain => Cow::Borrowed(reader.inner),
+            encoding => {
+                let mut tracks = Vec::new();
+                encoding.decoder(reader.inner, options)?.read_to_end(&mut tracks)?;
+                Cow::Owned(tracks)
+            },
+        };
+
+        let mut ranges = Vec::with_capacity(header.track_count.min(PREALLOCATION_LIMIT));
+        let mut start = 0;
+        for _ in 0..header.track_count {
+            let end = start + track_len(&tracks[start..], options, reader.position + start as u64)?;
+            ranges.push(start..end);
+            start = end;
+        }
+        if options.reject_trailing_data && start < tracks.len() {
+            return Err(AnimXError::TrailingData);
+        }
+
+        let mut output = Animation {
+            name: header.name,
+            global_duration: header.global_duration,
+            tracks: ranges.into_par_iter()
+                .map(|range| {
+                    let position = reader.position + range.start as u64;
+                    Self::read_track(&mut AnimXReader { inner: &tracks[range], options: options.clone(), position })
+                })
+                .collect::<Result<_, _>>()?,
+            extra: serde_json::Map::new(),
+        };
+        output.handle_unsorted(options)?;
+
+        Ok(output)
+    }
+}
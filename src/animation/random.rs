@@ -0,0 +1,215 @@
+//! Random animation generation, meant for tests, fuzzing and benchmarks
+//!
+//! Only available with the ``testing`` feature, the ``proptest`` feature additionally provides a ``proptest`` strategy
+
+use rand::{Rng, distr::Alphanumeric, seq::IndexedRandom};
+use serde::Serialize;
+
+use super::{Animation, BezierData, BezierKeyframe, CurveData, CurveKeyframe, DiscreteData, DiscreteKeyframe, Interpolation, RawData, Track, types::*};
+
+/// Describes the shape of an animation made by [``Animation::random``]
+#[derive(Debug, Clone)]
+pub struct RandomSpec {
+    /// Amount of tracks in the animation
+    pub tracks: usize,
+    /// Amount of keyframes in every track
+    pub keyframes: usize,
+    /// Track types to pick from, curves and beziers fall back to discrete tracks for value types that can't be interpolated
+    pub track_types: Vec<TrackType>,
+    /// Value types to pick from
+    pub value_types: Vec<ValueType>,
+}
+
+impl Default for RandomSpec {
+    fn default() -> Self {
+        Self {
+            tracks: 8,
+            keyframes: 16,
+            track_types: vec![TrackType::Raw, TrackType::Discrete, TrackType::Curve, TrackType::Bezier],
+            value_types: VALUE_TYPES.to_vec(),
+        }
+    }
+}
+
+const VALUE_TYPES: [ValueType; 40] = [
+    ValueType::Byte, ValueType::Ushort, ValueType::Ulong, ValueType::Sbyte, ValueType::Short,
+    ValueType::Bool, ValueType::Bool2, ValueType::Bool3, ValueType::Bool4,
+    ValueType::Int, ValueType::Int2, ValueType::Int3, ValueType::Int4,
+    ValueType::Uint, ValueType::Uint2, ValueType::Uint3, ValueType::Uint4,
+    ValueType::Long, ValueType::Long2, ValueType::Long3, ValueType::Long4,
+    ValueType::Float, ValueType::Float2, ValueType::Float3, ValueType::Float4,
+    ValueType::FloatQ, ValueType::Float2x2, ValueType::Float3x3, ValueType::Float4x4,
+    ValueType::Double, ValueType::Double2, ValueType::Double3, ValueType::Double4,
+    ValueType::DoubleQ, ValueType::Double2x2, ValueType::Double3x3, ValueType::Double4x4,
+    ValueType::Color, ValueType::Color32, ValueType::OptString,
+];
+
+impl Animation {
+    /// Generates a random but valid animation shaped by ``spec``
+    ///
+    /// Keyframe times are strictly increasing and ``global_duration`` covers every keyframe.
+    ///
+    /// ```
+    /// use rand::{SeedableRng, rngs::StdRng};
+    /// use resonite_core::animation::{Animation, random::RandomSpec};
+    ///
+    /// let anim = Animation::random(&mut StdRng::seed_from_u64(0), &RandomSpec::default());
+    /// assert_eq!(anim.tracks.len(), 8);
+    /// ```
+    pub fn random(rng: &mut impl Rng, spec: &RandomSpec) -> Animation {
+        let mut output = Animation {
+            name: Some(format!("Random{}", rng.random::<u16>())),
+            ..Default::default()
+        };
+
+        let mut duration: f32 = 0.0;
+        for i in 0..spec.tracks {
+            let track_type = *spec.track_types.choose(rng).expect("spec has no track types");
+            let value_type = *spec.value_types.choose(rng).expect("spec has no value types");
+            let node = Some(format!("Node{i}"));
+            let property = Some(format!("Property{i}"));
+
+            let (track, end) = metamatch::metamatch!(match value_type {
+                #[expand(for T in [
+                    Byte, Ushort, Ulong, Sbyte, Short,
+                    Bool, Bool2, Bool3, Bool4,
+                    Int, Int2, Int3, Int4,
+                    Uint, Uint2, Uint3, Uint4,
+                    Long, Long2, Long3, Long4,
+                    Float, Float2, Float3, Float4,
+                    FloatQ, Float2x2, Float3x3, Float4x4,
+                    Double, Double2, Double3, Double4,
+                    DoubleQ, Double2x2, Double3x3, Double4x4,
+                    Color, Color32, OptString,
+                ])]
+                ValueType::T => random_track::<T>(rng, spec.keyframes, track_type, value_type, node, property),
+            });
+            output.tracks.push(track);
+            duration = duration.max(end);
+        }
+        output.global_duration = Some(duration);
+
+        output
+    }
+}
+
+/// Makes a random track, returning it together with the time of its last keyframe
+fn random_track<T>(rng: &mut impl Rng, frames: usize, track_type: TrackType, value_type: ValueType, node: Option<String>, property: Option<String>) -> (Box<dyn TrackTrait>, f32)
+    where T: RandomValue + ValueTrait + Serialize + 'static
+{
+    let track_type = match track_type {
+        TrackType::Curve | TrackType::Bezier if !value_type.is_interpolatable() => TrackType::Discrete,
+        track_type => track_type,
+    };
+
+    let mut time = 0.0;
+    let mut times = Vec::with_capacity(frames);
+    for _ in 0..frames {
+        times.push(time);
+        time += rng.random_range(0.01..0.5);
+    }
+    let end = times.last().copied().unwrap_or_default();
+
+    match track_type {
+        TrackType::Raw => {
+            let interval = rng.random_range(0.01..0.1);
+            let keyframes = (0..frames).map(|_| T::random(rng)).collect();
+            let track = Track { track_type, value_type, data: RawData { node, property, interval: Some(interval), keyframes } };
+            (Box::new(track), interval * frames.saturating_sub(1) as f32)
+        },
+        TrackType::Discrete => {
+            let keyframes = times.into_iter().map(|time| DiscreteKeyframe { time, value: T::random(rng) }).collect();
+            let track = Track { track_type, value_type, data: DiscreteData { node, property, keyframes } };
+            (Box::new(track), end)
+        },
+        TrackType::Curve => {
+            let keyframes = times.into_iter().map(|time| CurveKeyframe {
+                time,
+                value: T::random(rng),
+                interpolation: if rng.random() { Interpolation::Linear } else { Interpolation::Hold },
+                left_tangent: None,
+                right_tangent: None,
+            }).collect();
+            let track = Track { track_type, value_type, data: CurveData { node, property, keyframes } };
+            (Box::new(track), end)
+        },
+        TrackType::Bezier => {
+            let keyframes = times.into_iter().map(|time| BezierKeyframe {
+                time,
+                value: T::random(rng),
+                left_control: T::random(rng),
+                right_control: T::random(rng),
+            }).collect();
+            let track = Track { track_type, value_type, data: BezierData { node, property, keyframes } };
+            (Box::new(track), end)
+        },
+    }
+}
+
+/// Generates a random [``Animation``] for every seed, using ``spec`` for its shape
+#[cfg(feature = "proptest")]
+pub fn animation_strategy(spec: RandomSpec) -> impl proptest::strategy::Strategy<Value = Animation> {
+    use proptest::strategy::Strategy;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    proptest::arbitrary::any::<u64>().prop_map(move |seed| Animation::random(&mut StdRng::seed_from_u64(seed), &spec))
+}
+
+trait RandomValue {
+    fn random(rng: &mut impl Rng) -> Self;
+}
+
+metamatch::quote! {
+    [<for T in [Byte, Ushort, Ulong, Sbyte, Short, Bool, Int, Uint, Long, Float, Double]>]
+        impl RandomValue for [<ident(str(T))>] {
+            fn random(rng: &mut impl Rng) -> Self {
+                rng.random()
+            }
+        }
+    [</for>]
+}
+
+metamatch::quote! {
+    [<for name in [Bool, Int, Uint, Long, Float, Double]>]
+        [<for range in 2..5>]
+            impl RandomValue for [<ident(str(name) + str(range))>] {
+                fn random(rng: &mut impl Rng) -> Self {
+                    Self {
+                        [<for field in 0..range>]
+                            [<let field_name = [x,y,z,w][field]>]
+                            [<ident(str(field_name))>]: rng.random(),
+                        [</for>]
+                    }
+                }
+            }
+        [</for>]
+    [</for>]
+}
+
+metamatch::quote! {
+    [<for name in [Color, Color32]>]
+        impl RandomValue for [<ident(str(name))>] {
+            fn random(rng: &mut impl Rng) -> Self {
+                Self {
+                    [<for field in [r,g,b,a]>]
+                        [<ident(str(field))>]: rng.random(),
+                    [</for>]
+                }
+            }
+        }
+    [</for>]
+}
+
+/// Matrices are just nested arrays
+impl<T, const N: usize> RandomValue for [T; N] where T: RandomValue {
+    fn random(rng: &mut impl Rng) -> Self {
+        std::array::from_fn(|_| T::random(rng))
+    }
+}
+
+impl RandomValue for OptString {
+    fn random(rng: &mut impl Rng) -> Self {
+        let len = rng.random_range(0..16);
+        OptString((0..len).map(|_| rng.sample(Alphanumeric) as char).collect())
+    }
+}
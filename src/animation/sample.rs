@@ -0,0 +1,410 @@
+//! Sampling of keyframed tracks
+
+use alloc::{borrow::ToOwned, boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use core::any::Any;
+
+#[cfg(not(feature = "std"))]
+use crate::float::FloatExt;
+
+use super::{Animation, BezierData, CurveData, DiscreteData, Interpolation, KeyframeTrait, RawData, Track, convert::along, types::{Blend, Float3, FloatQ, Lerp, ValueTrait}};
+
+#[allow(private_bounds)]
+impl<D> Track<D> where D: KeyframeTrait {
+    /// How values of the track are blended, a slerp for rotations and a lerp for everything else
+    pub(crate) fn blend(&self) -> Blend<D::Value> {
+        if self.value_type.is_quaternion() { slerp_values } else { Lerp::lerp }
+    }
+
+    /// Samples the data of the track, slerping if the track is a rotation
+    pub(crate) fn sample_blended(&self, time: f32) -> Option<D::Value> {
+        self.data.sample_with(time, self.blend())
+    }
+}
+
+metamatch::quote! {
+    [<for data in [RawData, DiscreteData, BezierData]>]
+        #[allow(private_bounds)]
+        impl<T> Track<[<ident(str(data))>]<T>> where T: ValueTrait {
+            /// Samples the track at ``time``, slerping rotations, see ``sample`` on curve tracks for how each kind of track is sampled
+            pub fn sample(&self, time: f32) -> Option<T> {
+                self.sample_blended(time)
+            }
+        }
+    [</for>]
+}
+
+#[allow(private_bounds)]
+impl<T> Track<CurveData<T>> where T: ValueTrait {
+    /// Samples the track at ``time``, how depends on the kind of track:
+    ///
+    /// - Raw tracks have a frame every ``interval`` seconds and lerp between them
+    /// - Discrete tracks step, keeping the value of the last keyframe at or before ``time``
+    /// - Curve tracks follow the interpolation of the keyframe before ``time``, ``Tangent`` & ``CubicBezier`` use the tangents
+    /// - Bezier tracks follow their control points
+    ///
+    /// ``floatq`` & ``doubleq`` tracks are rotations, so they're slerped instead of lerped.\
+    /// Every value type can be sampled, but bools & strings can't be interpolated so they always step, and integers are rounded.
+    ///
+    /// Times before the first or after the last keyframe are clamped, an empty track (or raw track without an interval) returns ``None``
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, CurveData, RawData, Track, types::{Float, Float3, FloatQ}};
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Raw", "valueType": "float", "data": {"node": "n", "property": "a", "interval": 0.5, "keyframes": [0.0, 2.0, 3.0]}},
+    ///     {"trackType": "Curve", "valueType": "float3", "data": {"node": "n", "property": "b", "keyframes": [
+    ///         {"time": 0.0, "value": {"x": 0.0, "y": 0.0, "z": 0.0}, "interpolation": "Tangent",
+    ///             "leftTangent": [-1.0, {"x": 0.0, "y": 0.0, "z": 0.0}], "rightTangent": [1.0, {"x": 0.0, "y": 0.0, "z": 0.0}]},
+    ///         {"time": 1.0, "value": {"x": 1.0, "y": 2.0, "z": 0.0}, "interpolation": "Hold",
+    ///             "leftTangent": [-1.0, {"x": 0.0, "y": 0.0, "z": 0.0}], "rightTangent": [1.0, {"x": 0.0, "y": 0.0, "z": 0.0}]}
+    ///     ]}},
+    ///     {"trackType": "Curve", "valueType": "floatq", "data": {"node": "n", "property": "c", "keyframes": [
+    ///         {"time": 0.0, "value": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0}, "interpolation": "Linear"},
+    ///         {"time": 1.0, "value": {"x": 0.0, "y": 0.70710677, "z": 0.0, "w": 0.70710677}, "interpolation": "Linear"}
+    ///     ]}}
+    /// ]}"#).unwrap();
+    ///
+    /// let raw = anim.tracks[0].as_any().downcast_ref::<Track<RawData<Float>>>().unwrap();
+    /// assert_eq!(raw.sample(0.75), Some(2.5)); // Halfway between the frames at 0.5 and 1.0
+    ///
+    /// // Flat tangents on both ends make a smoothstep: 3t² - 2t³
+    /// let curve = anim.tracks[1].as_any().downcast_ref::<Track<CurveData<Float3>>>().unwrap();
+    /// let sampled = curve.sample(0.25).unwrap();
+    /// assert!((sampled.x - 0.15625).abs() < 1e-6);
+    /// assert!((sampled.y - 0.3125).abs() < 1e-6);
+    ///
+    /// // Halfway to a 90° turn around Y is a 45° turn, which a lerp wouldn't give
+    /// let rotation = anim.tracks[2].as_any().downcast_ref::<Track<CurveData<FloatQ>>>().unwrap();
+    /// let sampled = rotation.sample(0.5).unwrap();
+    /// assert!((sampled.y - 22.5f32.to_radians().sin()).abs() < 1e-6);
+    /// assert!((sampled.w - 22.5f32.to_radians().cos()).abs() < 1e-6);
+    /// ```
+    pub fn sample(&self, time: f32) -> Option<T> {
+        self.sample_blended(time)
+    }
+}
+
+/// Spherical interpolation between two rotations, along the shortest path
+///
+/// This is what ``floatq`` tracks are sampled with, the result is always a unit quaternion.
+///
+/// ```
+/// use resonite_core::animation::{normalize, slerp, types::FloatQ};
+///
+/// let identity = FloatQ { x: 0.0, y: 0.0, z: 0.0, w: 1.0 };
+/// let half = 45f32.to_radians();
+/// let quarter_turn = FloatQ { x: 0.0, y: half.sin(), z: 0.0, w: half.cos() };
+///
+/// // Halfway to a 90° turn is a 45° turn, so the half angle is 22.5°
+/// let q = slerp(identity, quarter_turn, 0.5);
+/// assert!((q.y - 22.5f32.to_radians().sin()).abs() < 1e-6);
+/// assert!((q.w - 22.5f32.to_radians().cos()).abs() < 1e-6);
+///
+/// // -q is the same rotation as q, the short way there is not moving at all
+/// let flipped = FloatQ { x: -0.0, y: -0.0, z: -0.0, w: -1.0 };
+/// assert_eq!(slerp(identity, flipped, 0.5).w, 1.0);
+///
+/// let q = normalize(FloatQ { x: 0.0, y: 2.0, z: 0.0, w: 2.0 });
+/// assert!((q.y - half.sin()).abs() < 1e-6 && (q.w - half.cos()).abs() < 1e-6);
+/// ```
+pub fn slerp(a: FloatQ, b: FloatQ, t: f32) -> FloatQ {
+    slerp_values(&a, &b, t)
+}
+
+/// Scales ``q`` to a length of 1, a zero quaternion stays zero
+pub fn normalize(q: FloatQ) -> FloatQ {
+    normalize_value(&q)
+}
+
+fn slerp_values<T>(a: &T, b: &T, t: f32) -> T where T: ValueTrait {
+    // q and -q are the same rotation, flipping one of them when they point away from each other takes the short way around
+    let dot = a.dot(b);
+    let (b, dot) = if dot < 0.0 { (b.add_scaled(b, -2.0), -dot) } else { (b.clone(), dot) };
+
+    // Nearly the same rotation, sin(angle) gets too small to divide by
+    if dot > 0.9995 {
+        return normalize_value(&a.lerp(&b, t));
+    }
+
+    let angle = dot.min(1.0).acos();
+    let sin = angle.sin();
+    let (wa, wb) = (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin);
+    a.add_scaled(a, wa - 1.0).add_scaled(&b, wb)
+}
+
+fn normalize_value<T>(q: &T) -> T where T: ValueTrait {
+    let length = q.dot(q).sqrt();
+    if length > 0.0 { q.add_scaled(q, 1.0 / length - 1.0) } else { q.clone() }
+}
+
+#[allow(private_bounds)]
+impl<T> CurveData<T> where T: ValueTrait {
+    /// Samples the curve at ``time``
+    /// 
+    /// Each keyframe's interpolation decides how the segment up to the next keyframe is sampled:\
+    /// ``Hold`` is a step, keeping the keyframe's value right up until the next keyframe's time, and ``Linear`` lerps between the two.\
+    /// ``Tangent`` and ``CubicBezier`` follow the right tangent of the keyframe and the left tangent of the next one,
+    /// the same curve [``CurveData::to_bezier``] makes. A missing tangent points straight at the other keyframe.
+    /// 
+    /// Times before the first or after the last keyframe are clamped to those keyframes, an empty curve returns ``None``
+    ///
+    /// ```
+    /// use resonite_core::animation::{CurveData, CurveKeyframe};
+    ///
+    /// let keyframes = vec![CurveKeyframe::hold(0.0, 1.0f32), CurveKeyframe::hold(1.0, 2.0), CurveKeyframe::linear(2.0, 3.0)];
+    /// let curve = CurveData { node: None, property: None, keyframes };
+    ///
+    /// // Between two Hold keyframes the earlier value stays right up until the next keyframe
+    /// assert_eq!(curve.sample(0.5), Some(1.0));
+    /// assert_eq!(curve.sample(1.0 - 1e-4), Some(1.0));
+    /// assert_eq!(curve.sample(1.0), Some(2.0));
+    /// assert_eq!(curve.sample(2.0 - 1e-4), Some(2.0));
+    /// ```
+    pub fn sample(&self, time: f32) -> Option<T> {
+        self.sample_with(time, T::lerp)
+    }
+
+    pub(crate) fn sample_with(&self, time: f32, blend: Blend<T>) -> Option<T> {
+        let first = self.keyframes.first()?;
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return Some(first.value.clone());
+        }
+
+        let current = &self.keyframes[next - 1];
+        let Some(next) = self.keyframes.get(next) else {
+            return Some(current.value.clone());
+        };
+
+        let dt = next.time - current.time;
+        let t = (time - current.time) / dt;
+        match current.interpolation {
+            Interpolation::Hold => Some(current.value.clone()),
+            Interpolation::Linear => Some(blend(&current.value, &next.value, t)),
+            Interpolation::Tangent | Interpolation::CubicBezier => {
+                let right = current.right_tangent.as_ref()
+                    .map_or_else(|| current.value.lerp(&next.value, 1.0 / 3.0), |tangent| along(&current.value, tangent, dt / 3.0));
+                let left = next.left_tangent.as_ref()
+                    .map_or_else(|| next.value.lerp(&current.value, 1.0 / 3.0), |tangent| along(&next.value, tangent, -dt / 3.0));
+                Some(cubic(&current.value, &right, &left, &next.value, t, blend))
+            },
+        }
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> RawData<T> where T: ValueTrait {
+    /// Samples the track at ``time``, lerping between the two frames around it
+    /// 
+    /// Times outside of the track are clamped to its first or last frame, an empty track or one without an interval returns ``None``
+    pub fn sample(&self, time: f32) -> Option<T> {
+        self.sample_with(time, T::lerp)
+    }
+
+    pub(crate) fn sample_with(&self, time: f32, blend: Blend<T>) -> Option<T> {
+        let interval = self.interval?;
+        let last = self.keyframes.len().checked_sub(1)?;
+        if interval <= 0.0 {
+            return Some(self.keyframes[0].clone());
+        }
+
+        let position = (time / interval).clamp(0.0, last as f32);
+        let index = position.floor() as usize;
+        let next = (index + 1).min(last);
+        Some(blend(&self.keyframes[index], &self.keyframes[next], position - index as f32))
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> DiscreteData<T> where T: ValueTrait {
+    /// Samples the track at ``time``, which is the value of the last keyframe at or before ``time``
+    /// 
+    /// Times before the first keyframe return the first keyframe's value, an empty track returns ``None``
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let index = self.keyframes.partition_point(|k| k.time <= time).saturating_sub(1);
+        self.keyframes.get(index).map(|k| k.value.clone())
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> BezierData<T> where T: ValueTrait {
+    /// Samples the bezier segments at ``time``
+    ///
+    /// Times before the first or after the last keyframe are clamped to those keyframes, an empty track returns ``None``
+    pub fn sample(&self, time: f32) -> Option<T> {
+        self.sample_with(time, T::lerp)
+    }
+
+    pub(crate) fn sample_with(&self, time: f32, blend: Blend<T>) -> Option<T> {
+        let first = self.keyframes.first()?;
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return Some(first.value.clone());
+        }
+
+        let current = &self.keyframes[next - 1];
+        let Some(next) = self.keyframes.get(next) else {
+            return Some(current.value.clone());
+        };
+
+        let t = (time - current.time) / (next.time - current.time);
+        Some(cubic(&current.value, &current.right_control, &next.left_control, &next.value, t, blend))
+    }
+}
+
+/// Point ``t`` of the way along a cubic bezier segment (de Casteljau)\
+/// The control points are evenly spaced in time, so ``t`` can be used for the curve directly
+fn cubic<T>(start: &T, right: &T, left: &T, end: &T, t: f32, blend: Blend<T>) -> T where T: ValueTrait {
+    let a = blend(start, right, t);
+    let b = blend(right, left, t);
+    let c = blend(left, end, t);
+    let (d, e) = (blend(&a, &b, t), blend(&b, &c, t));
+    blend(&d, &e, t)
+}
+
+impl Animation {
+    /// Samples every track at ``fps`` frames per second, turning them into discrete tracks with a keyframe on every frame
+    ///
+    /// Frames go from 0 up to the duration of the animation (``global_duration``, or the last keyframe if it's not set),
+    /// bools and strings keep stepping like they did before.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, DiscreteData, Track, types::Float};
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"globalDuration": 1.0, "tracks": [{"trackType": "Curve", "valueType": "float",
+    ///     "data": {"node": "n", "property": "p", "keyframes": [
+    ///         {"time": 0.0, "value": 0.0, "interpolation": "Linear"}, {"time": 1.0, "value": 2.0, "interpolation": "Linear"}
+    ///     ]}}]}"#).unwrap();
+    ///
+    /// let downsampled = anim.downsample_to_fps(10.0);
+    /// let track = downsampled.tracks[0].as_any().downcast_ref::<Track<DiscreteData<Float>>>().unwrap();
+    /// assert_eq!(track.data.keyframes.len(), 11);
+    /// assert_eq!(track.data.keyframes[0].time, 0.0);
+    /// assert_eq!(track.data.keyframes[10].time, 1.0);
+    /// assert!((track.data.keyframes[5].value - 1.0).abs() < 1e-6);
+    /// ```
+    pub fn downsample_to_fps(&self, fps: f32) -> Animation {
+        assert!(fps > 0.0, "fps has to be positive");
+        let duration = self.duration();
+        let frames = (duration * fps).round().max(0.0) as usize;
+        let times: Vec<f32> = (0..=frames).map(|frame| (frame as f32 / fps).min(duration)).collect();
+
+        Animation {
+            name: self.name.clone(),
+            global_duration: self.global_duration,
+            tracks: self.tracks.iter().map(|track| track.sample_to_discrete(&times)).collect(),
+            extra: self.extra.clone(),
+        }
+    }
+
+    /// Bakes every track into a raw track with a frame every ``1 / fps`` seconds, from 0 up to the duration of the animation
+    ///
+    /// Rotations are slerped and everything else lerped, the same as [``Track::sample``](super::Track::sample).\
+    /// Bool and string tracks are copied as they are, a raw track would blend them.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, RawData, Track, types::{Float, FloatQ, TrackType}};
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Curve", "valueType": "float", "data": {"node": "n", "property": "a", "keyframes": [
+    ///         {"time": 0.0, "value": 0.0, "interpolation": "Linear"}, {"time": 1.0, "value": 2.0, "interpolation": "Linear"}
+    ///     ]}},
+    ///     {"trackType": "Discrete", "valueType": "bool", "data": {"node": "n", "property": "b", "keyframes": [{"time": 0.0, "value": true}]}},
+    ///     {"trackType": "Curve", "valueType": "floatq", "data": {"node": "n", "property": "c", "keyframes": [
+    ///         {"time": 0.0, "value": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0}, "interpolation": "Linear"},
+    ///         {"time": 1.0, "value": {"x": 0.0, "y": 1.0, "z": 0.0, "w": 0.0}, "interpolation": "Linear"}
+    ///     ]}}
+    /// ]}"#).unwrap();
+    ///
+    /// let baked = anim.resample(10.0);
+    /// let ramp = baked.tracks[0].as_any().downcast_ref::<Track<RawData<Float>>>().unwrap();
+    /// assert_eq!(ramp.data.interval, Some(0.1));
+    /// assert_eq!(ramp.data.keyframes.len(), 11);
+    /// for (frame, value) in ramp.data.keyframes.iter().enumerate() {
+    ///     assert!((value - frame as f32 * 0.2).abs() < 1e-6);
+    /// }
+    ///
+    /// assert_eq!(baked.tracks[1].track_type(), TrackType::Discrete);
+    ///
+    /// // Halfway through a 180° turn is a 90° turn
+    /// let rotation = baked.tracks[2].as_any().downcast_ref::<Track<RawData<FloatQ>>>().unwrap();
+    /// assert!((rotation.data.keyframes[5].y - 45f32.to_radians().sin()).abs() < 1e-6);
+    /// ```
+    pub fn resample(&self, fps: f32) -> Animation {
+        assert!(fps > 0.0, "fps has to be positive");
+        let frames = (self.duration() * fps).round().max(0.0) as usize + 1;
+
+        Animation {
+            name: self.name.clone(),
+            global_duration: self.global_duration,
+            tracks: self.tracks.iter().map(|track| track.sample_to_raw(1.0 / fps, frames).unwrap_or_else(|| track.clone_box())).collect(),
+            extra: self.extra.clone(),
+        }
+    }
+
+    /// Samples every track at ``time``, grouping the values by node
+    ///
+    /// Tracks without a node or property are skipped
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Discrete", "valueType": "floatq", "data": {"node": "Head", "property": "Rotation", "keyframes": [
+    ///         {"time": 0.0, "value": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0}}
+    ///     ]}},
+    ///     {"trackType": "Curve", "valueType": "float3", "data": {"node": "Hips", "property": "Position", "keyframes": [
+    ///         {"time": 0.0, "value": {"x": 0.0, "y": 1.0, "z": 0.0}, "interpolation": "Linear"},
+    ///         {"time": 1.0, "value": {"x": 2.0, "y": 1.0, "z": 0.0}, "interpolation": "Linear"}
+    ///     ]}}
+    /// ]}"#).unwrap();
+    ///
+    /// let pose = anim.sample_pose(0.5);
+    /// assert_eq!(pose.rotation("Head").unwrap().w, 1.0);
+    /// assert_eq!(pose.position("Hips").unwrap().x, 1.0);
+    /// assert!(pose.position("Head").is_none());
+    /// ```
+    pub fn sample_pose(&self, time: f32) -> Pose {
+        let mut pose = Pose::default();
+        for track in &self.tracks {
+            let (Some(node), Some(property)) = (track.node(), track.property()) else { continue };
+            if let Some(value) = track.sample_any(time) {
+                pose.nodes.entry(node.to_owned()).or_default().insert(property.to_owned(), value);
+            }
+        }
+        pose
+    }
+}
+
+/// The values of every track at one point in time, made by [``Animation::sample_pose``]
+#[derive(Debug, Default)]
+pub struct Pose {
+    nodes: BTreeMap<String, BTreeMap<String, Box<dyn Any>>>,
+}
+
+impl Pose {
+    /// Every node with at least one value
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.nodes.keys().map(String::as_str)
+    }
+
+    /// The value of ``property`` on ``node``, ``None`` if there isn't one or it isn't a ``T``
+    pub fn get<T>(&self, node: &str, property: &str) -> Option<T> where T: Clone + 'static {
+        self.nodes.get(node)?.get(property)?.downcast_ref::<T>().cloned()
+    }
+
+    /// The ``Position`` of ``node``
+    pub fn position(&self, node: &str) -> Option<Float3> {
+        self.get(node, "Position")
+    }
+
+    /// The ``Rotation`` of ``node``
+    pub fn rotation(&self, node: &str) -> Option<FloatQ> {
+        self.get(node, "Rotation")
+    }
+
+    /// The ``Scale`` of ``node``
+    pub fn scale(&self, node: &str) -> Option<Float3> {
+        self.get(node, "Scale")
+    }
+}
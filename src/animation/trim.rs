@@ -0,0 +1,212 @@
+//! Cutting a time range out of an animation, see [``Animation::trim``]
+
+#[cfg(not(feature = "std"))]
+use crate::float::FloatExt;
+
+use super::{Animation, BezierData, BezierKeyframe, CurveData, CurveKeyframe, DiscreteData, DiscreteKeyframe, Interpolation, RawData, convert::along, types::{Blend, ValueTrait}};
+
+impl Animation {
+    /// Copies the part of the animation between ``start`` and ``end`` seconds, moved so it starts at 0
+    ///
+    /// Keyframes in the range are kept and every track gets a keyframe at ``start`` and ``end``,
+    /// curve and bezier tracks have the segments that are cut through split so they keep their shape.\
+    /// Raw tracks are sampled again from ``start``, so their last frame can fall a bit short of ``end``.
+    /// ``global_duration`` becomes ``end - start``, tracks without any keyframes stay empty.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, CurveKeyframe, DiscreteData, DiscreteKeyframe, Track, types::Float};
+    ///
+    /// let anim = Animation::builder()
+    ///     .global_duration(10.0)
+    ///     .add_curve_track("n", "ramp", vec![CurveKeyframe::linear(0.0, 0.0f32), CurveKeyframe::linear(10.0, 10.0)])
+    ///     .add_discrete_track("n", "steps", (0..=10).map(|i| DiscreteKeyframe { time: i as f32, value: i as f32 }).collect())
+    ///     .add_discrete_track("n", "outside", vec![DiscreteKeyframe { time: 8.0, value: 1.0f32 }])
+    ///     .finish();
+    ///
+    /// let clip = anim.trim(2.0, 5.0);
+    /// assert_eq!(clip.global_duration, Some(3.0));
+    ///
+    /// let ramp = &clip.tracks[0];
+    /// assert_eq!(ramp.keyframe_count(), 2);
+    /// assert_eq!(*ramp.sample_any(1.5).unwrap().downcast::<f32>().unwrap(), 3.5);
+    ///
+    /// let steps = clip.tracks[1].downcast::<Track<DiscreteData<Float>>>().unwrap();
+    /// let times: Vec<_> = steps.data.keyframes.iter().map(|k| (k.time, k.value)).collect();
+    /// assert_eq!(times, [(0.0, 2.0), (1.0, 3.0), (2.0, 4.0), (3.0, 5.0)]);
+    ///
+    /// // Nothing happens between 2 and 5, but the value at both ends is still there
+    /// assert_eq!(clip.tracks[2].keyframe_count(), 2);
+    /// assert_eq!(clip.tracks[2].end_time(), 3.0);
+    /// ```
+    pub fn trim(&self, start: f32, end: f32) -> Animation {
+        assert!(start <= end, "start has to come before end");
+        Animation {
+            name: self.name.clone(),
+            global_duration: Some(end - start),
+            tracks: self.tracks.iter().map(|track| track.trim(start, end)).collect(),
+            extra: self.extra.clone(),
+        }
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> RawData<T> where T: ValueTrait {
+    /// Samples the frames from ``start`` up to ``end`` again, at the same interval
+    pub(crate) fn trim(&mut self, start: f32, end: f32, blend: Blend<T>) {
+        let Some(interval) = self.interval.filter(|interval| *interval > 0.0) else { return };
+        if self.keyframes.is_empty() {
+            return;
+        }
+
+        let frames = ((end - start) / interval).floor() as usize + 1;
+        self.keyframes = (0..frames).filter_map(|frame| self.sample_with(start + frame as f32 * interval, blend)).collect();
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> DiscreteData<T> where T: ValueTrait {
+    pub(crate) fn trim(&mut self, start: f32, end: f32) {
+        if self.keyframes.is_empty() {
+            return;
+        }
+
+        self.insert_sampled_keyframe(start);
+        self.insert_sampled_keyframe(end);
+        self.keyframes.retain(|k| (start..=end).contains(&k.time));
+        self.keyframes.iter_mut().for_each(|k| k.time -= start);
+    }
+
+    /// Adds a keyframe at ``time`` with the value the track has there, if there isn't one already
+    pub(crate) fn insert_sampled_keyframe(&mut self, time: f32) {
+        if let Err(index) = self.keyframes.binary_search_by(|k| k.time.total_cmp(&time)) {
+            let Some(value) = self.sample(time) else { return };
+            self.keyframes.insert(index, DiscreteKeyframe { time, value });
+        }
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> CurveData<T> where T: ValueTrait {
+    pub(crate) fn trim(&mut self, start: f32, end: f32, blend: Blend<T>) {
+        if self.keyframes.is_empty() {
+            return;
+        }
+
+        self.insert_sampled_keyframe(start, blend);
+        self.insert_sampled_keyframe(end, blend);
+        self.keyframes.retain(|k| (start..=end).contains(&k.time));
+        self.keyframes.iter_mut().for_each(|k| k.time -= start);
+    }
+
+    /// Adds a keyframe at ``time`` without changing the shape of the curve, if there isn't one already
+    pub(crate) fn insert_sampled_keyframe(&mut self, time: f32, blend: Blend<T>) {
+        let index = match self.keyframes.binary_search_by(|k| k.time.total_cmp(&time)) {
+            Ok(_) => return,
+            Err(_) if self.keyframes.is_empty() => return,
+            Err(index) => index,
+        };
+
+        if index == 0 {
+            let value = self.keyframes[0].value.clone();
+            self.keyframes.insert(0, CurveKeyframe::hold(time, value));
+            return;
+        }
+        if index == self.keyframes.len() {
+            // The last keyframe didn't lead anywhere before, now it has to stay flat
+            let last = self.keyframes.last_mut().unwrap();
+            last.interpolation = Interpolation::Hold;
+            let value = last.value.clone();
+            self.keyframes.push(CurveKeyframe::hold(time, value));
+            return;
+        }
+
+        let (current, next) = (&self.keyframes[index - 1], &self.keyframes[index]);
+        let dt = next.time - current.time;
+        let t = (time - current.time) / dt;
+        let keyframe = match current.interpolation {
+            Interpolation::Hold => CurveKeyframe::hold(time, current.value.clone()),
+            Interpolation::Linear => CurveKeyframe::linear(time, blend(&current.value, &next.value, t)),
+            Interpolation::Tangent | Interpolation::CubicBezier => {
+                let right = current.right_tangent.as_ref()
+                    .map_or_else(|| current.value.lerp(&next.value, 1.0 / 3.0), |tangent| along(&current.value, tangent, dt / 3.0));
+                let left = next.left_tangent.as_ref()
+                    .map_or_else(|| next.value.lerp(&current.value, 1.0 / 3.0), |tangent| along(&next.value, tangent, -dt / 3.0));
+                let [a, d, value, e, c] = split_cubic(&current.value, &right, &left, &next.value, t, blend);
+
+                // Tangents reach a third of the way into their segment, which is now shorter on both sides
+                let (before, after) = (t * dt / 3.0, (1.0 - t) * dt / 3.0);
+                let right_tangent = (before, a.add_scaled(&current.value, -1.0));
+                let left_tangent = (-after, c.add_scaled(&next.value, -1.0));
+                self.keyframes[index - 1].right_tangent = Some(right_tangent);
+                self.keyframes[index].left_tangent = Some(left_tangent);
+
+                CurveKeyframe {
+                    time,
+                    left_tangent: Some((-before, d.add_scaled(&value, -1.0))),
+                    right_tangent: Some((after, e.add_scaled(&value, -1.0))),
+                    value,
+                    interpolation: self.keyframes[index - 1].interpolation,
+                }
+            },
+        };
+        self.keyframes.insert(index, keyframe);
+    }
+}
+
+#[allow(private_bounds)]
+impl<T> BezierData<T> where T: ValueTrait {
+    pub(crate) fn trim(&mut self, start: f32, end: f32, blend: Blend<T>) {
+        if self.keyframes.is_empty() {
+            return;
+        }
+
+        self.insert_sampled_keyframe(start, blend);
+        self.insert_sampled_keyframe(end, blend);
+        self.keyframes.retain(|k| (start..=end).contains(&k.time));
+        self.keyframes.iter_mut().for_each(|k| k.time -= start);
+    }
+
+    /// Adds a keyframe at ``time`` without changing the shape of the curve, if there isn't one already
+    pub(crate) fn insert_sampled_keyframe(&mut self, time: f32, blend: Blend<T>) {
+        let index = match self.keyframes.binary_search_by(|k| k.time.total_cmp(&time)) {
+            Ok(_) => return,
+            Err(_) if self.keyframes.is_empty() => return,
+            Err(index) => index,
+        };
+        let flat = |time: f32, value: &T| BezierKeyframe { time, value: value.clone(), left_control: value.clone(), right_control: value.clone() };
+
+        // The controls on the outer side of the first and last keyframe weren't used before, they have to be flat now
+        if index == 0 {
+            let first = &mut self.keyframes[0];
+            first.left_control = first.value.clone();
+            let keyframe = flat(time, &first.value);
+            self.keyframes.insert(0, keyframe);
+            return;
+        }
+        if index == self.keyframes.len() {
+            let last = self.keyframes.last_mut().unwrap();
+            last.right_control = last.value.clone();
+            let keyframe = flat(time, &last.value);
+            self.keyframes.push(keyframe);
+            return;
+        }
+
+        let (current, next) = (&self.keyframes[index - 1], &self.keyframes[index]);
+        let t = (time - current.time) / (next.time - current.time);
+        let [a, d, value, e, c] = split_cubic(&current.value, &current.right_control, &next.left_control, &next.value, t, blend);
+        self.keyframes[index - 1].right_control = a;
+        self.keyframes[index].left_control = c;
+        self.keyframes.insert(index, BezierKeyframe { time, value, left_control: d, right_control: e });
+    }
+}
+
+/// Splits a cubic bezier segment ``t`` of the way along (de Casteljau)\
+/// Gives the new right control of ``start``, the left control, value and right control of the new point, and the new left control of ``end``
+fn split_cubic<T>(start: &T, right: &T, left: &T, end: &T, t: f32, blend: Blend<T>) -> [T; 5] where T: ValueTrait {
+    let a = blend(start, right, t);
+    let b = blend(right, left, t);
+    let c = blend(left, end, t);
+    let (d, e) = (blend(&a, &b, t), blend(&b, &c, t));
+    let value = blend(&d, &e, t);
+    [a, d, value, e, c]
+}
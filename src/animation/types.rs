@@ -1,24 +1,417 @@
 //! Not for the faint of heart
+//!
+//! Vectors can be made with ``new``, or converted from tuples and arrays:
+//!
+//! ```
+//! use resonite_core::animation::types::Float3;
+//!
+//! let a = Float3::new(1.0, 2.0, 3.0);
+//! let b = Float3::from((1.0, 2.0, 3.0));
+//! let c: Float3 = [1.0, 2.0, 3.0].into();
+//! for v in [a, b, c] {
+//!     assert_eq!((v.x, v.y, v.z), (1.0, 2.0, 3.0));
+//! }
+//! ```
 
-use std::{fmt::Debug, io::Read};
-use serde::Deserialize;
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{any::{Any, TypeId}, fmt::Debug};
+use serde::{Deserialize, Serialize};
 
-use super::{AnimXError, AnimXReader};
+#[cfg(not(feature = "std"))]
+use crate::float::FloatExt;
+use crate::io::{self, Read};
+use super::{AnimXError, AnimXReader, AnimXWriter, OptStringEncoding, ValidationError, Value};
 
-// This trait is kinda funny, but I didn't want to deal with passing around an ``impl Write<W>`` since ``dyn TrackTrait`` got quite angry about it
+// Writing goes through ``AnimXWriter`` instead of an ``impl Write<W>`` since ``dyn TrackTrait`` got quite angry about it
 pub(crate) trait WriteBytes where Self: Debug {
-    fn write(&self, write: &mut dyn FnMut(&[u8]));
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()>;
 }
 pub(crate) trait ReadBytes where Self: Sized {
     fn read(reader: &mut AnimXReader<impl Read>) -> Result<Self, AnimXError>;
 }
 
+/// Linear interpolation between two values, used when sampling tracks
+pub trait Lerp where Self: Sized {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
 // These traits aren't great... oh well
 #[allow(private_bounds)]
-pub trait TrackTrait where Self: WriteBytes + Debug {}
-pub(crate) trait KeyframeTrait where Self: WriteBytes + Debug {}
+pub trait TrackTrait where Self: WriteBytes + Debug + Send + Sync {
+    /// Node the track animates
+    fn node(&self) -> Option<&str>;
+
+    /// Changes the node the track animates, see [``Animation::rename_nodes``](super::Animation::rename_nodes)
+    fn set_node(&mut self, node: Option<String>);
+
+    /// Property of the node the track animates
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Raw", "valueType": "float3", "data": {"node": "Hips", "property": "Position", "interval": 0.1, "keyframes": []}},
+    ///     {"trackType": "Curve", "valueType": "floatq", "data": {"node": "Hips", "property": "Rotation", "keyframes": []}},
+    ///     {"trackType": "Discrete", "valueType": "bool", "data": {"node": "Hat", "property": "Enabled", "keyframes": []}}
+    /// ]}"#).unwrap();
+    ///
+    /// let properties: Vec<_> = anim.tracks.iter().filter_map(|t| t.property()).collect();
+    /// assert_eq!(properties, ["Position", "Rotation", "Enabled"]);
+    /// ```
+    fn property(&self) -> Option<&str>;
+
+    /// Kind of track, which decides how the keyframes are stored and sampled
+    fn track_type(&self) -> TrackType;
+
+    /// Type of the values in the track
+    fn value_type(&self) -> ValueType;
+
+    /// Gives access to the concrete ``Track`` behind the trait object
+    fn as_any(&self) -> &dyn Any;
+
+    /// Copies the track into a new box, which is what makes ``Box<dyn TrackTrait>`` (and [``Animation``](super::Animation)) ``Clone``
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"name": "Walk", "tracks": [
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "n", "property": "p", "keyframes": [{"time": 1.0, "value": 1.0}]}}
+    /// ]}"#).unwrap();
+    ///
+    /// let mut copy = anim.clone();
+    /// copy.name = Some("Run".to_owned());
+    /// copy.tracks[0].scale_times(2.0);
+    /// copy.tracks.push(anim.tracks[0].clone_box());
+    ///
+    /// assert_eq!(anim.name.as_deref(), Some("Walk"));
+    /// assert_eq!(anim.tracks.len(), 1);
+    /// assert_eq!(anim.tracks[0].end_time(), 1.0);
+    /// assert_eq!(copy.tracks[0].end_time(), 2.0);
+    /// assert_eq!(anim.tracks[0].to_animj().unwrap(), copy.tracks[1].to_animj().unwrap());
+    /// ```
+    fn clone_box(&self) -> Box<dyn TrackTrait>;
+
+    /// Converts the track to its AnimJ representation
+    fn to_animj(&self) -> serde_json::Result<serde_json::Value>;
+
+    /// Amount of keyframes (or frames for raw tracks) in the track
+    fn keyframe_count(&self) -> usize;
+
+    /// Value of the keyframe at ``index``, without having to know the value type of the track
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, Value, types::Float3};
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Raw", "valueType": "float3", "data": {"node": "n", "property": "a", "interval": 0.1, "keyframes": [{"x": 1.0, "y": 2.0, "z": 3.0}]}},
+    ///     {"trackType": "Discrete", "valueType": "string", "data": {"node": "n", "property": "b", "keyframes": [{"time": 0.0, "value": "Hi"}]}},
+    ///     {"trackType": "Curve", "valueType": "floatq", "data": {"node": "n", "property": "c", "keyframes": [
+    ///         {"time": 0.0, "value": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0}, "interpolation": "Linear"}
+    ///     ]}},
+    ///     {"trackType": "Discrete", "valueType": "int", "data": {"node": "n", "property": "d", "keyframes": []}}
+    /// ]}"#).unwrap();
+    ///
+    /// assert!(matches!(anim.tracks[0].value_at(0), Some(Value::Float3(Float3 { x: 1.0, y: 2.0, z: 3.0 }))));
+    /// assert!(matches!(anim.tracks[1].value_at(0), Some(Value::OptString(s)) if s.0 == "Hi"));
+    /// assert!(matches!(anim.tracks[2].value_at(0), Some(Value::FloatQ(q)) if q.w == 1.0));
+    /// assert!(anim.tracks[2].value_at(1).is_none());
+    /// assert!(anim.tracks[3].value_at(0).is_none());
+    /// ```
+    fn value_at(&self, index: usize) -> Option<Value>;
+
+    /// Time of the keyframe at ``index``, for raw tracks that's ``interval * index``
+    fn keyframe_time(&self, index: usize) -> Option<f32>;
+
+    /// Index of the last keyframe at or before ``time``, ``None`` if the track is empty or ``time`` comes before the first keyframe
+    ///
+    /// This is a binary search, so the keyframes have to be sorted (see [``TrackTrait::is_sorted``])
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, DiscreteKeyframe};
+    ///
+    /// let keyframes = [0.5, 1.0, 2.0].map(|time| DiscreteKeyframe { time, value: 1.0f32 }).to_vec();
+    /// let anim = Animation::builder()
+    ///     .add_discrete_track("n", "a", keyframes)
+    ///     .add_raw_track("n", "b", 0.25, vec![1.0f32; 4])
+    ///     .finish();
+    /// let (discrete, raw) = (&anim.tracks[0], &anim.tracks[1]);
+    ///
+    /// // Exact hits
+    /// assert_eq!(discrete.keyframe_index_at(1.0), Some(1));
+    /// assert_eq!(raw.keyframe_index_at(0.5), Some(2));
+    /// // Between keyframes
+    /// assert_eq!(discrete.keyframe_index_at(1.5), Some(1));
+    /// assert_eq!(raw.keyframe_index_at(0.3), Some(1));
+    /// // Before the first and after the last
+    /// assert_eq!(discrete.keyframe_index_at(0.0), None);
+    /// assert_eq!(raw.keyframe_index_at(-0.1), None);
+    /// assert_eq!(discrete.keyframe_index_at(10.0), Some(2));
+    /// assert_eq!(raw.keyframe_index_at(10.0), Some(3));
+    ///
+    /// assert_eq!(discrete.keyframe_time(2), Some(2.0));
+    /// assert_eq!(raw.keyframe_time(3), Some(0.75));
+    /// assert_eq!(raw.keyframe_time(4), None);
+    /// ```
+    fn keyframe_index_at(&self, time: f32) -> Option<usize>;
+
+    /// Time between the frames of a raw track, ``None`` for discrete & curve tracks
+    ///
+    /// ```
+    /// use resonite_core::animation::Animation;
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Raw", "valueType": "float", "data": {"node": "n", "property": "a", "interval": 0.25, "keyframes": [0.0]}},
+    ///     {"trackType": "Discrete", "valueType": "float", "data": {"node": "n", "property": "b", "keyframes": []}}
+    /// ]}"#).unwrap();
+    /// assert_eq!(anim.tracks[0].interval(), Some(0.25));
+    /// assert_eq!(anim.tracks[1].interval(), None);
+    /// ```
+    fn interval(&self) -> Option<f32>;
+
+    /// Shifts the time of every keyframe by ``delta`` seconds\
+    /// Raw tracks don't store a start time, so they're left untouched
+    fn offset_times(&mut self, delta: f32);
+
+    /// Same as [``TrackTrait::offset_times``], except raw tracks are delayed by repeating their first frame, rounded to whole frames\
+    /// Raw tracks can't start before 0, so a negative ``delta`` leaves them untouched
+    /// At most 2^20 frames are added, which is about 3 hours at 90 fps
+    fn shift_times(&mut self, delta: f32);
+
+    /// Multiplies the time of every keyframe (and the interval of raw tracks) by ``factor``\
+    /// Tangent times are scaled too, which keeps the shape of the curve
+    fn scale_times(&mut self, factor: f32);
+
+    /// Time of the last keyframe, for raw tracks this is ``interval * (keyframes - 1)``
+    fn end_time(&self) -> f32;
+
+    /// Time of the first keyframe, raw and empty tracks start at 0
+    fn start_time(&self) -> f32;
+
+    /// Removes every keyframe after ``time``
+    fn truncate(&mut self, time: f32);
+
+    /// Adds a keyframe at ``time`` with the value the track has there, if there isn't one at exactly that time already
+    ///
+    /// Curve and bezier tracks have the segment ``time`` falls in split, so the shape of the curve stays the same.
+    /// Before the first or after the last keyframe the new keyframe holds the value of that keyframe.\
+    /// Raw tracks can't have a frame at any time and empty tracks have nothing to sample, both are left untouched.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, CurveData, Track};
+    ///
+    /// let mut anim: Animation = serde_json::from_str(r#"{"tracks": [{"trackType": "Curve", "valueType": "float", "data": {"node": "n", "property": "p", "keyframes": [
+    ///     {"time": 0.0, "value": 0.0, "interpolation": "Tangent", "leftTangent": [-1.0, 0.0], "rightTangent": [0.25, 2.0]},
+    ///     {"time": 1.0, "value": 1.0, "interpolation": "Linear", "leftTangent": [-0.5, -1.0], "rightTangent": [1.0, 0.0]},
+    ///     {"time": 2.0, "value": 3.0, "interpolation": "Hold"}
+    /// ]}}]}"#).unwrap();
+    /// let sample = |anim: &Animation, time: f32| *anim.tracks[0].sample_any(time).unwrap().downcast::<f32>().unwrap();
+    /// let times = [-1.0, 0.0, 0.1, 0.3, 0.35, 0.6, 0.99, 1.0, 1.5, 2.0, 3.0];
+    /// let before = times.map(|time| sample(&anim, time));
+    ///
+    /// anim.tracks[0].insert_sampled_keyframe(0.3);
+    /// anim.tracks[0].insert_sampled_keyframe(1.5);
+    /// anim.tracks[0].insert_sampled_keyframe(1.0); // Already there
+    /// let curve = anim.tracks[0].downcast::<Track<CurveData<f32>>>().unwrap();
+    /// assert_eq!(curve.data.keyframes.iter().map(|k| k.time).collect::<Vec<_>>(), [0.0, 0.3, 1.0, 1.5, 2.0]);
+    /// for (time, before) in times.into_iter().zip(before) {
+    ///     assert!((sample(&anim, time) - before).abs() < 1e-5, "{time}");
+    /// }
+    /// ```
+    fn insert_sampled_keyframe(&mut self, time: f32);
+
+    /// Whether the keyframes are sorted by time, which is always the case for raw tracks
+    fn is_sorted(&self) -> bool;
+
+    /// Sorts the keyframes by time, keeping the order of keyframes with the same time
+    fn sort_keyframes(&mut self);
+
+    /// Multiplies every value by ``factor``\
+    /// Returns ``false`` (leaving the track untouched) if the track isn't numeric, which includes bools, strings, colors and quaternions
+    fn scale_values(&mut self, factor: f32) -> bool;
+
+    /// Indices of the keyframes with a NaN or infinite time, value or tangent
+    fn nonfinite_keyframes(&self) -> Vec<usize>;
+
+    /// Adds the problems with this track to ``errors``, ``index`` being the index of the track in its animation
+    fn validate(&self, index: usize, errors: &mut Vec<ValidationError>);
+
+    /// Adds the keyframes of ``other`` to this track, keeping this track's keyframe where both have one at the same time\
+    /// Returns ``false`` (leaving the track untouched) if ``other`` isn't the same kind of track with the same value type, or either is a raw track
+    fn merge_keyframes(&mut self, other: &dyn TrackTrait) -> bool;
+
+    /// Adds the frames of the raw track ``other`` after the frames of this raw track, starting ``start`` seconds in\
+    /// ``start`` is rounded to whole frames and can't be before the end of this track, the last frame is held until then.
+    /// Returns ``false`` (leaving the track untouched) if ``other`` isn't the same kind of track, or either isn't a raw track with the same interval
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, RawData, Track, types::Float};
+    ///
+    /// let mut anim = Animation::builder()
+    ///     .add_raw_track("n", "p", 0.5, vec![1.0f32, 2.0])
+    ///     .add_raw_track("n", "p", 0.5, vec![3.0f32])
+    ///     .add_raw_track("n", "p", 0.25, vec![4.0f32])
+    ///     .finish();
+    /// let (a, rest) = anim.tracks.split_at_mut(1);
+    /// assert!(a[0].append_frames(rest[0].as_ref(), 1.5));
+    /// assert!(!a[0].append_frames(rest[1].as_ref(), 1.5));
+    ///
+    /// let track = anim.tracks[0].downcast::<Track<RawData<Float>>>().unwrap();
+    /// assert_eq!(track.data.keyframes, [1.0, 2.0, 2.0, 3.0]);
+    /// ```
+    fn append_frames(&mut self, other: &dyn TrackTrait, start: f32) -> bool;
+
+    /// Samples the track at ``time``, the value can be downcast to the track's value type\
+    /// Returns ``None`` for empty tracks (and raw tracks without an interval)
+    fn sample_any(&self, time: f32) -> Option<Box<dyn Any>>;
+
+    /// Samples the track at each of ``times``, making a discrete track with a keyframe for every sample\
+    /// An empty track results in an empty discrete track
+    fn sample_to_discrete(&self, times: &[f32]) -> Box<dyn TrackTrait>;
+
+    /// Samples the track every ``interval`` seconds, making a raw track of ``frames`` frames\
+    /// Returns ``None`` for bools and strings, a raw track would blend them
+    fn sample_to_raw(&self, interval: f32, frames: usize) -> Option<Box<dyn TrackTrait>>;
+
+    /// Removes keyframes that are within ``tolerance`` of what the keyframes around them give anyway, returning how many were removed
+    ///
+    /// The first and last keyframe always stay.
+    /// Discrete tracks and ``Hold`` keyframes drop values that repeat the one before them,
+    /// runs of ``Linear`` keyframes are simplified like a line in Ramer–Douglas–Peucker and ``Tangent`` & ``CubicBezier`` keyframes are all kept.\
+    /// Raw, bezier, bool and string tracks are left untouched.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, CurveKeyframe};
+    ///
+    /// let ramp = (0..100).map(|i| CurveKeyframe::linear(i as f32, i as f32 * 2.0)).collect();
+    /// let mut anim = Animation::builder().add_curve_track("n", "p", ramp).finish();
+    ///
+    /// assert_eq!(anim.tracks[0].decimate(1e-3), 98);
+    /// assert_eq!(anim.tracks[0].keyframe_count(), 2);
+    /// assert_eq!(anim.tracks[0].end_time(), 99.0);
+    ///
+    /// // A bump in the middle has to stay, along with the keyframes next to it
+    /// let bump = [0.0f32, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0].into_iter().enumerate().map(|(i, v)| CurveKeyframe::linear(i as f32, v)).collect();
+    /// let mut anim = Animation::builder().add_curve_track("n", "p", bump).finish();
+    /// anim.tracks[0].decimate(0.1);
+    /// assert_eq!(anim.tracks[0].keyframe_count(), 5);
+    /// assert_eq!(*anim.tracks[0].sample_any(3.0).unwrap().downcast::<f32>().unwrap(), 5.0);
+    /// ```
+    fn decimate(&mut self, tolerance: f32) -> usize;
+
+    /// Copies the part of the track between ``start`` and ``end`` seconds, moved to start at 0, see [``Animation::trim``](super::Animation::trim)
+    fn trim(&self, start: f32, end: f32) -> Box<dyn TrackTrait>;
 
-#[derive(Debug, Deserialize, Clone, Copy)]
+    /// Whether ``other`` is the same kind of track for the same target, with every number within ``epsilon``, see [``Animation::approx_eq``](super::Animation::approx_eq)
+    fn approx_eq(&self, other: &dyn TrackTrait, epsilon: f32) -> bool;
+
+    /// Makes a copy of the track with its values converted to ``value_type``, which works between the ``Float*`` and ``Double*`` version of a type\
+    /// Returns ``None`` for any other pair of types
+    fn convert_value_type(&self, value_type: ValueType) -> Option<Box<dyn TrackTrait>>;
+}
+impl Clone for Box<dyn TrackTrait> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl dyn TrackTrait {
+    /// Shorthand for ``as_any().downcast_ref()``, ``D`` being the concrete ``Track`` type
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, DiscreteData, RawData, Track, types::Float};
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Raw", "valueType": "float", "data": {"node": "n", "property": "p", "interval": 0.5, "keyframes": [1.0, 2.0]}}
+    /// ]}"#).unwrap();
+    ///
+    /// let track = anim.tracks[0].downcast::<Track<RawData<Float>>>().unwrap();
+    /// assert_eq!(track.data.keyframes, [1.0, 2.0]);
+    /// assert!(anim.tracks[0].downcast::<Track<DiscreteData<Float>>>().is_none());
+    /// ```
+    pub fn downcast<D>(&self) -> Option<&D> where D: 'static {
+        self.as_any().downcast_ref()
+    }
+}
+
+pub(crate) trait KeyframeTrait where Self: WriteBytes + Debug + Send + Sync {
+    fn node(&self) -> Option<&str>;
+    fn set_node(&mut self, node: Option<String>);
+    fn property(&self) -> Option<&str>;
+    fn keyframe_count(&self) -> usize;
+    fn value_at(&self, index: usize) -> Option<&Self::Value>;
+    fn keyframe_time(&self, index: usize) -> Option<f32>;
+    fn keyframe_index_at(&self, time: f32) -> Option<usize> {
+        let (mut low, mut high) = (0, self.keyframe_count());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.keyframe_time(middle)? <= time {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        low.checked_sub(1)
+    }
+    fn interval(&self) -> Option<f32> { None }
+    fn offset_times(&mut self, delta: f32);
+    fn shift_times(&mut self, delta: f32) { self.offset_times(delta) }
+    fn scale_times(&mut self, factor: f32);
+    fn end_time(&self) -> f32;
+    fn start_time(&self) -> f32 { 0.0 }
+    fn truncate(&mut self, time: f32);
+    fn insert_sampled_keyframe(&mut self, _time: f32, _blend: Blend<Self::Value>) {}
+    fn is_sorted(&self) -> bool;
+    fn sort_keyframes(&mut self);
+    fn scale_values(&mut self, factor: f32) -> bool;
+    fn nonfinite_keyframes(&self) -> Vec<usize>;
+    fn validate(&self, _track: usize, _errors: &mut Vec<ValidationError>) {}
+    fn merge(&mut self, _other: &Self) -> bool { false }
+    fn append(&mut self, _other: &Self, _start: f32) -> bool { false }
+    fn decimate(&mut self, _tolerance: f32, _blend: Blend<Self::Value>) -> usize { 0 }
+    fn trim(&mut self, start: f32, end: f32, blend: Blend<Self::Value>);
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool;
+
+    type Value: ValueTrait;
+    fn sample_with(&self, time: f32, blend: Blend<Self::Value>) -> Option<Self::Value>;
+}
+
+/// How two values are blended while sampling, [``Lerp::lerp``] for most tracks and a slerp for rotations
+pub(crate) type Blend<T> = fn(&T, &T, f32) -> T;
+
+/// Things every keyframe value type can do, so tracks can be edited without knowing their value type
+pub(crate) trait ValueTrait where Self: WriteBytes + Debug + Lerp + Clone + Serialize + Send + Sync + 'static {
+    /// Multiplies the value by ``factor``, returns ``false`` (leaving the value alone) for non-numeric types
+    fn scale(&mut self, _factor: f32) -> bool { false }
+
+    /// Whether every float in the value is neither NaN nor infinite, always ``true`` for non-float types
+    fn is_finite(&self) -> bool { true }
+
+    /// ``self + other * factor``, component-wise for vectors, colors and matrices\
+    /// Non-numeric types just return a copy of ``self``
+    fn add_scaled(&self, _other: &Self, _factor: f32) -> Self { self.clone() }
+
+    /// Dot product, only used to slerp quaternions so non-float types just return 0
+    fn dot(&self, _other: &Self) -> f32 { 0.0 }
+
+    /// Euclidean distance to ``other``, infinite for non-numeric types so they're never close to anything
+    fn distance(&self, _other: &Self) -> f32 { f32::INFINITY }
+
+    /// Whether ``other`` is within ``epsilon`` of the value, non-numeric types have to be equal
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool { self.distance(other) <= epsilon }
+}
+
+/// How the keyframes of a track are stored, the discriminant is the byte written to AnimX
+///
+/// ```
+/// use resonite_core::animation::types::TrackType;
+///
+/// for byte in 0..=3 {
+///     assert_eq!(TrackType::try_from(byte).unwrap() as u8, byte);
+/// }
+/// assert_eq!(TrackType::try_from(3), Ok(TrackType::Bezier));
+/// assert!(TrackType::try_from(4).is_err());
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
 pub enum TrackType {
     Raw,
     Discrete,
@@ -34,20 +427,29 @@ impl TryFrom<u8> for TrackType {
             0 => Ok(Self::Raw),
             1 => Ok(Self::Discrete),
             2 => Ok(Self::Curve),
+            3 => Ok(Self::Bezier),
             _ => Err(()),
         }
     }
 }
 
-#[derive(Debug, Deserialize, Clone, Copy)]
+/// Type of the values in a track, the discriminant is the byte written to AnimX so the order has to match its ``TryFrom<u8>``
+///
+/// ```
+/// use resonite_core::animation::types::ValueType;
+///
+/// for value_type in [ValueType::Byte, ValueType::Bool, ValueType::Int4, ValueType::Uint, ValueType::Long2, ValueType::Float, ValueType::OptString] {
+///     assert_eq!(ValueType::try_from(value_type as u8), Ok(value_type));
+/// }
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ValueType {
+    Byte, Ushort, Ulong, Sbyte, Short,
     Bool, Bool2, Bool3, Bool4,
-    Byte, Ushort, Uint, Ulong,
-    Sbyte, Short, Int, Long,
-    Int2, Int3, Int4,
-    Uint2, Uint3, Uint4,
-    Long2, Long3, Long4,
+    Int, Int2, Int3, Int4,
+    Uint, Uint2, Uint3, Uint4,
+    Long, Long2, Long3, Long4,
     Float, Float2, Float3, Float4,
     FloatQ, Float2x2, Float3x3, Float4x4,
     Double, Double2, Double3, Double4,
@@ -80,15 +482,144 @@ impl TryFrom<u8> for ValueType {
     }
 }
 
+impl ValueType {
+    /// Amount of scalars in a value, a ``Float3x3`` has 9 and colors have 4\
+    /// Strings count as a single component
+    pub const fn component_count(self) -> usize {
+        use ValueType::*;
+        match self {
+            Byte | Ushort | Ulong | Sbyte | Short | Bool | Int | Uint | Long | Float | Double | OptString => 1,
+            Bool2 | Int2 | Uint2 | Long2 | Float2 | Double2 => 2,
+            Bool3 | Int3 | Uint3 | Long3 | Float3 | Double3 => 3,
+            Bool4 | Int4 | Uint4 | Long4 | Float4 | FloatQ | Double4 | DoubleQ | Float2x2 | Double2x2 | Color | Color32 => 4,
+            Float3x3 | Double3x3 => 9,
+            Float4x4 | Double4x4 => 16,
+        }
+    }
+
+    /// Amount of bytes a value takes up in AnimX, ``None`` for strings since their length varies\
+    /// Bool vectors are packed into a single byte, one bit per component
+    ///
+    /// ```
+    /// use resonite_core::animation::types::ValueType;
+    ///
+    /// assert_eq!(ValueType::Short.size_bytes(), Some(2));
+    /// assert_eq!(ValueType::Float3.size_bytes(), Some(12));
+    /// assert_eq!(ValueType::Double4x4.size_bytes(), Some(128));
+    /// assert_eq!(ValueType::Color32.size_bytes(), Some(4));
+    /// assert_eq!(ValueType::Bool3.size_bytes(), Some(1));
+    /// assert_eq!(ValueType::OptString.size_bytes(), None);
+    ///
+    /// assert_eq!(ValueType::Short.component_count(), 1);
+    /// assert_eq!(ValueType::Float3.component_count(), 3);
+    /// assert_eq!(ValueType::Double4x4.component_count(), 16);
+    /// assert_eq!(ValueType::Color32.component_count(), 4);
+    /// ```
+    pub const fn size_bytes(self) -> Option<usize> {
+        use ValueType::*;
+        let scalar = match self {
+            Bool | Bool2 | Bool3 | Bool4 => return Some(1),
+            OptString => return None,
+            Byte | Sbyte | Color32 => 1,
+            Ushort | Short => 2,
+            Int | Int2 | Int3 | Int4 | Uint | Uint2 | Uint3 | Uint4 => 4,
+            Float | Float2 | Float3 | Float4 | FloatQ | Float2x2 | Float3x3 | Float4x4 | Color => 4,
+            Ulong | Long | Long2 | Long3 | Long4 => 8,
+            Double | Double2 | Double3 | Double4 | DoubleQ | Double2x2 | Double3x3 | Double4x4 => 8,
+        };
+        Some(scalar * self.component_count())
+    }
+
+    /// Single numbers and bools, strings don't count
+    pub const fn is_scalar(self) -> bool {
+        use ValueType::*;
+        matches!(self, Byte | Ushort | Ulong | Sbyte | Short | Bool | Int | Uint | Long | Float | Double)
+    }
+
+    /// Vectors of 2 to 4 components\
+    /// Quaternions are stored as 4 component vectors but aren't counted as one, neither are colors
+    ///
+    /// ```
+    /// use resonite_core::animation::types::ValueType;
+    ///
+    /// assert!(ValueType::Float.is_scalar());
+    /// assert!(ValueType::Bool3.is_vector());
+    /// assert!(ValueType::Float3x3.is_matrix());
+    /// assert!(ValueType::FloatQ.is_quaternion());
+    /// assert!(!ValueType::FloatQ.is_vector());
+    /// assert!(ValueType::Color32.is_color());
+    /// assert!(!ValueType::OptString.is_scalar());
+    /// ```
+    pub const fn is_vector(self) -> bool {
+        !self.is_quaternion() && !self.is_matrix() && !self.is_color() && self.component_count() > 1
+    }
+
+    /// Square matrices, ``Float2x2`` up to ``Double4x4``
+    pub const fn is_matrix(self) -> bool {
+        use ValueType::*;
+        matches!(self, Float2x2 | Float3x3 | Float4x4 | Double2x2 | Double3x3 | Double4x4)
+    }
+
+    /// ``FloatQ`` & ``DoubleQ``, which are sampled with a slerp
+    pub const fn is_quaternion(self) -> bool {
+        matches!(self, ValueType::FloatQ | ValueType::DoubleQ)
+    }
+
+    /// ``Color`` & ``Color32``
+    pub const fn is_color(self) -> bool {
+        matches!(self, ValueType::Color | ValueType::Color32)
+    }
+
+    /// Whether values of this type can be interpolated, bools & strings can't\
+    /// Curves of them can only ``Hold`` and never have tangents, the AnimX reader, writer and [``Animation::validate``](super::Animation::validate) all go by this
+    ///
+    /// ```
+    /// use resonite_core::animation::types::ValueType;
+    ///
+    /// assert!(ValueType::Float3.is_interpolatable());
+    /// assert!(ValueType::Int.is_interpolatable());
+    /// assert!(!ValueType::Bool2.is_interpolatable());
+    /// assert!(!ValueType::OptString.is_interpolatable());
+    /// ```
+    pub const fn is_interpolatable(self) -> bool {
+        use ValueType::*;
+        !matches!(self, Bool | Bool2 | Bool3 | Bool4 | OptString)
+    }
+
+    /// The value type of ``T``, 4 component vectors are ``Float4``/``Double4`` since quaternions are the same type
+    pub(crate) fn of<T>() -> ValueType where T: ValueTrait {
+        let id = TypeId::of::<T>();
+        metamatch::quote! {
+            [<for V in [
+                Byte, Ushort, Ulong, Sbyte, Short,
+                Bool, Bool2, Bool3, Bool4,
+                Int, Int2, Int3, Int4,
+                Uint, Uint2, Uint3, Uint4,
+                Long, Long2, Long3, Long4,
+                Float, Float2, Float3, Float4,
+                Float2x2, Float3x3, Float4x4,
+                Double, Double2, Double3, Double4,
+                Double2x2, Double3x3, Double4x4,
+                Color, Color32, OptString,
+            ]>]
+                if id == TypeId::of::<[<ident(str(V))>]>() {
+                    return ValueType::[<ident(str(V))>];
+                }
+            [</for>]
+        }
+        unreachable!("every value type implements ValueTrait")
+    }
+}
+
 impl WriteBytes for ValueType {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        write(&[*self as u8]);
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        writer.write(&[*self as u8])
     }
 }
 
 impl<T> WriteBytes for Option<T> where T: WriteBytes + Default {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        self.as_ref().unwrap_or(&Default::default()).write(write);
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        self.as_ref().unwrap_or(&Default::default()).write(writer)
     }
 }
 
@@ -97,64 +628,95 @@ type VarInt = usize;
 
 /// Little hack to make writing structure byte lengths as varints easy
 impl WriteBytes for VarInt {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
         let mut value = *self;
         while value > 127 {
-            ((value & 127 | 128) as u8).write(write);
+            ((value & 127 | 128) as u8).write(writer)?;
             value >>= 7;
         }
 
-        (value as u8).write(write);
+        (value as u8).write(writer)
     }
 }
 
-impl WriteBytes for String {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
+impl WriteBytes for str {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
         let bytes = self.as_bytes();
-        bytes.len().write(write);
-        write(bytes);
+        bytes.len().write(writer)?;
+        writer.write(bytes)
     }
 }
 
-/// Since header strings don't write a nullable byte (even though they're optional!) this wrapper is used as the type for "string" tracks
+impl WriteBytes for String {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        self.as_str().write(writer)
+    }
+}
 
-// I honestly don't know why this is here, why not just denote empty strings with a size of 0? it would take up less space in the AnimX format
-#[derive(Deserialize, Debug, Default)]
+/// Type for the values of "string" tracks
+/// 
+/// String values in Resonite can be null, so unlike header strings they're written with a leading presence byte by default:
+/// ``0x00`` for a null string, or ``0x01`` followed by the length prefixed string.\
+/// This is the same for raw, discrete and curve tracks. Null strings and empty strings are both represented as an empty ``OptString``, and get written as null\
+/// Some versions might skip the presence byte, see [``OptStringEncoding``]
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
 pub struct OptString(pub String);
 
 impl WriteBytes for OptString {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
         let bytes = self.0.as_bytes();
-        if bytes.len() == 0 {
-            write(&[0x00]);
-            return;
+        if writer.options().opt_string_encoding == OptStringEncoding::LengthPrefixed {
+            bytes.len().write(writer)?;
+            return writer.write(bytes);
+        }
+        if bytes.is_empty() {
+            return writer.write(&[0x00]);
         }
-        write(&[0x01]);
-        bytes.len().write(write);
-        write(bytes);
+        writer.write(&[0x01])?;
+        bytes.len().write(writer)?;
+        writer.write(bytes)
     }
 }
 
 impl ReadBytes for OptString {
     fn read(reader: &mut AnimXReader<impl Read>) -> Result<Self, AnimXError> {
-        reader.read_nullable_string().map(|v| OptString(v.unwrap_or_default()))
+        match reader.options().opt_string_encoding {
+            OptStringEncoding::Flagged => reader.read_nullable_string().map(|v| OptString(v.unwrap_or_default())),
+            OptStringEncoding::LengthPrefixed => reader.read_string().map(OptString),
+        }
     }
 }
 
 metamatch::quote! {
     [<for (name, internal) in [(Color, f32), (Color32, u8)]>]
-        #[derive(Debug, Deserialize, Clone, Copy)]
+        /// RGBA color, ``Color`` has a float per channel and ``Color32`` a byte
+        ///
+        /// ```
+        /// use resonite_core::animation::types::{Color, Color32};
+        ///
+        /// let color = Color::new(1.0, 0.5, 0.0, 1.0);
+        /// assert_eq!(color.r, 1.0);
+        /// assert_eq!(Color32::new(255, 128, 0, 255).g, 128);
+        /// ```
+        #[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
         pub struct [<ident(str(name))>]  {
             [<for field in [r,g,b,a]>]
                 pub [<ident(str(field))>]: [<ident(str(internal))>],
             [</for>]
         }
+
+        impl [<ident(str(name))>] {
+            pub const fn new(r: [<ident(str(internal))>], g: [<ident(str(internal))>], b: [<ident(str(internal))>], a: [<ident(str(internal))>]) -> Self {
+                Self { r, g, b, a }
+            }
+        }
         
         impl WriteBytes for [<ident(str(name))>] {
-            fn write(&self, write: &mut dyn FnMut(&[u8])) {
+            fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
                 [<for field in [r,g,b,a]>]
-                    self.[<ident(str(field))>].write(write);
+                    self.[<ident(str(field))>].write(writer)?;
                 [</for>]
+                Ok(())
             }
         }
 
@@ -170,6 +732,41 @@ metamatch::quote! {
     [</for>]
 }
 
+/// Divides every channel by 255, no gamma is applied so the channels stay in whatever space they were in
+///
+/// ```
+/// use resonite_core::animation::types::{Color, Color32};
+///
+/// let color = Color::from(Color32::new(0, 255, 51, 128));
+/// assert_eq!((color.r, color.g, color.b), (0.0, 1.0, 0.2));
+/// assert_eq!(color.a, 128.0 / 255.0);
+/// ```
+impl From<Color32> for Color {
+    fn from(value: Color32) -> Self {
+        Self::new(value.r as f32 / 255.0, value.g as f32 / 255.0, value.b as f32 / 255.0, value.a as f32 / 255.0)
+    }
+}
+
+/// Clamps every channel to ``0..=1`` and scales it to ``0..=255``, rounding to the nearest byte\
+/// Like the other way around there's no gamma, NaN becomes 0
+///
+/// ```
+/// use resonite_core::animation::types::{Color, Color32};
+///
+/// let color = Color32::from(Color::new(0.0, 1.0, 0.5, 2.0));
+/// assert_eq!((color.r, color.g, color.b, color.a), (0, 255, 128, 255));
+/// assert_eq!(Color32::from(Color::new(-1.0, f32::NAN, 0.2, 0.0)).b, 51);
+///
+/// // Every byte survives the trip to float and back
+/// assert!((0..=255).all(|i| Color32::from(Color::from(Color32::new(i, i, i, i))).r == i));
+/// ```
+impl From<Color> for Color32 {
+    fn from(value: Color) -> Self {
+        let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Self::new(channel(value.r), channel(value.g), channel(value.b), channel(value.a))
+    }
+}
+
 pub type Byte = u8;
 pub type Ushort = u16;
 pub type Ulong = u64;
@@ -185,37 +782,39 @@ pub type Double3x3 = [[Double; 3]; 3];
 pub type Double4x4 = [[Double; 4]; 4];
 
 impl WriteBytes for Bool {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        write(&[if *self {1} else {0}]);
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        writer.write(&[if *self {1} else {0}])
     }
 }
 
 impl WriteBytes for Bool2 {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        write(&[if self.x {1} else {0} | if self.y {2} else {0}]);
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        writer.write(&[if self.x {1} else {0} | if self.y {2} else {0}])
     }
 }
 
 impl WriteBytes for Bool3 {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        write(&[if self.x {1} else {0} | if self.y {2} else {0} | if self.z {4} else {0}]);
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        writer.write(&[if self.x {1} else {0} | if self.y {2} else {0} | if self.z {4} else {0}])
     }
 }
 
 impl WriteBytes for Bool4 {
-    fn write(&self, write: &mut dyn FnMut(&[u8])) {
-        write(&[if self.x {1} else {0} | if self.y {2} else {0} | if self.z {4} else {0} | if self.w {8} else {0}]);
+    fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+        writer.write(&[if self.x {1} else {0} | if self.y {2} else {0} | if self.z {4} else {0} | if self.w {8} else {0}])
     }
 }
 
 impl ReadBytes for Bool {
     fn read(reader: &mut AnimXReader<impl Read>) -> Result<Self, AnimXError> {
-        Ok(reader.read_bool()?)
+        reader.read_bool()
     }
 }
 
 metamatch::quote! {
     [<for I in 2..5>]
+        // The x bit is shifted by 0 to line up with the others
+        #[allow(clippy::identity_op)]
         impl ReadBytes for [<ident("Bool" + str(I))>] {
             fn read(reader: &mut AnimXReader<impl Read>) -> Result<Self, AnimXError> {
                 let byte = reader.read_u8()?;
@@ -233,8 +832,8 @@ metamatch::quote! {
 metamatch::quote! {
     [<for (name, size) in [(Byte, 1), (Sbyte, 1), (Ushort, 2), (Ulong, 8), (Short, 2), (Int, 4), (Long, 8), (Uint, 4), (Float, 4), (Double, 8)]>]
         impl WriteBytes for [<ident(str(name))>] {
-            fn write(&self, write: &mut dyn FnMut(&[u8])) {
-                write(&self.to_le_bytes());
+            fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+                writer.write(&self.to_le_bytes())
             }
         }
 
@@ -253,8 +852,8 @@ metamatch::quote! {
         [<for size in 2..5>]
             [<let name = str(type) + str(size) + "x" + str(size)>]
             impl WriteBytes for [<ident(str(name))>] {
-                fn write(&self, write: &mut dyn FnMut(&[u8])) {
-                    self.iter().for_each(|i| i.iter().for_each(|i| i.write(write)))
+                fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
+                    self.iter().flatten().try_for_each(|i| i.write(writer))
                 }
             }
 
@@ -280,7 +879,7 @@ metamatch::quote! {
         pub type [<ident(str(name))>] = [<ident(str(internal))>];
 
         [<for range in 2..5>]
-            #[derive(Debug, Deserialize, Clone, Copy)]
+            #[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
             pub struct [<ident(str(name) + str(range))>] {
                 [<for field in 0..range>]
                     [<let field_name = [x,y,z,w][field]>]
@@ -288,13 +887,70 @@ metamatch::quote! {
                 [</for>]
             }
 
+            impl [<ident(str(name) + str(range))>] {
+                pub const fn new(
+                    [<for field in 0..range>]
+                        [<let field_name = [x,y,z,w][field]>]
+                        [<ident(str(field_name))>]: [<ident(str(internal))>],
+                    [</for>]
+                ) -> Self {
+                    Self {
+                        [<for field in 0..range>]
+                            [<let field_name = [x,y,z,w][field]>]
+                            [<ident(str(field_name))>],
+                        [</for>]
+                    }
+                }
+            }
+
+            impl From<(
+                [<for field in 0..range>]
+                    [<ident(str(internal))>],
+                [</for>]
+            )> for [<ident(str(name) + str(range))>] {
+                fn from((
+                    [<for field in 0..range>]
+                        [<let field_name = [x,y,z,w][field]>]
+                        [<ident(str(field_name))>],
+                    [</for>]
+                ): (
+                    [<for field in 0..range>]
+                        [<ident(str(internal))>],
+                    [</for>]
+                )) -> Self {
+                    Self::new(
+                        [<for field in 0..range>]
+                            [<let field_name = [x,y,z,w][field]>]
+                            [<ident(str(field_name))>],
+                        [</for>]
+                    )
+                }
+            }
+
+            impl From<[[<ident(str(internal))>]; [<(range)>]]> for [<ident(str(name) + str(range))>] {
+                fn from([
+                    [<for field in 0..range>]
+                        [<let field_name = [x,y,z,w][field]>]
+                        [<ident(str(field_name))>],
+                    [</for>]
+                ]: [[<ident(str(internal))>]; [<(range)>]]) -> Self {
+                    Self::new(
+                        [<for field in 0..range>]
+                            [<let field_name = [x,y,z,w][field]>]
+                            [<ident(str(field_name))>],
+                        [</for>]
+                    )
+                }
+            }
+
             [<if derive>]
             impl WriteBytes for [<ident(str(name) + str(range))>] {
-                fn write(&self, write: &mut dyn FnMut(&[u8])) {
+                fn write(&self, writer: &mut AnimXWriter) -> io::Result<()> {
                     [<for field in 0..range>]
                         [<let field_name = [x,y,z,w][field]>]
-                        write( &self.[<ident(str(field_name))>].to_le_bytes() );
+                        writer.write( &self.[<ident(str(field_name))>].to_le_bytes() )?;
                     [</for>]
+                    Ok(())
                 }
             }
 
@@ -312,3 +968,255 @@ metamatch::quote! {
         [</for>]
     [</for>]
 }
+
+impl Lerp for Float {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Double {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t as Double
+    }
+}
+
+metamatch::quote! {
+    [<for name in [Byte, Ushort, Ulong, Sbyte, Short, Int, Uint, Long]>]
+        impl Lerp for [<ident(str(name))>] {
+            fn lerp(&self, other: &Self, t: f32) -> Self {
+                (*self as Double).lerp(&(*other as Double), t).round() as Self
+            }
+        }
+    [</for>]
+}
+
+// Bools and strings can't be interpolated, so they step to ``other`` at the very end
+metamatch::quote! {
+    [<for name in [Bool, Bool2, Bool3, Bool4]>]
+        impl Lerp for [<ident(str(name))>] {
+            fn lerp(&self, other: &Self, t: f32) -> Self {
+                if t < 1.0 { *self } else { *other }
+            }
+        }
+    [</for>]
+}
+
+impl Lerp for OptString {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        if t < 1.0 { self.clone() } else { other.clone() }
+    }
+}
+
+/// Matrices are just nested arrays, so they get lerped component-wise
+impl<T, const N: usize> Lerp for [T; N] where T: Lerp {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        core::array::from_fn(|i| self[i].lerp(&other[i], t))
+    }
+}
+
+metamatch::quote! {
+    [<for (name, fields) in [
+        (Int2, [x,y]), (Int3, [x,y,z]), (Int4, [x,y,z,w]),
+        (Uint2, [x,y]), (Uint3, [x,y,z]), (Uint4, [x,y,z,w]),
+        (Long2, [x,y]), (Long3, [x,y,z]), (Long4, [x,y,z,w]),
+        (Float2, [x,y]), (Float3, [x,y,z]), (Float4, [x,y,z,w]),
+        (Double2, [x,y]), (Double3, [x,y,z]), (Double4, [x,y,z,w]),
+        (Color, [r,g,b,a]), (Color32, [r,g,b,a]),
+    ]>]
+        impl Lerp for [<ident(str(name))>] {
+            fn lerp(&self, other: &Self, t: f32) -> Self {
+                Self {
+                    [<for field in fields>]
+                        [<ident(str(field))>]: self.[<ident(str(field))>].lerp(&other.[<ident(str(field))>], t),
+                    [</for>]
+                }
+            }
+        }
+    [</for>]
+}
+
+metamatch::quote! {
+    [<for name in [Bool, Bool2, Bool3, Bool4, OptString]>]
+        impl ValueTrait for [<ident(str(name))>] {
+            fn approx_eq(&self, other: &Self, _epsilon: f32) -> bool {
+                self == other
+            }
+        }
+    [</for>]
+}
+
+impl ValueTrait for Color {
+    fn is_finite(&self) -> bool {
+        self.r.is_finite() && self.g.is_finite() && self.b.is_finite() && self.a.is_finite()
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        [(self.r, other.r), (self.g, other.g), (self.b, other.b), (self.a, other.a)].iter().map(|(a, b)| (a - b).powi(2)).sum::<f32>().sqrt()
+    }
+
+    fn add_scaled(&self, other: &Self, factor: f32) -> Self {
+        Self {
+            r: self.r.add_scaled(&other.r, factor),
+            g: self.g.add_scaled(&other.g, factor),
+            b: self.b.add_scaled(&other.b, factor),
+            a: self.a.add_scaled(&other.a, factor),
+        }
+    }
+}
+
+impl ValueTrait for Color32 {
+    fn distance(&self, other: &Self) -> f32 {
+        [(self.r, other.r), (self.g, other.g), (self.b, other.b), (self.a, other.a)].iter().map(|(a, b)| a.distance(b).powi(2)).sum::<f32>().sqrt()
+    }
+
+    fn add_scaled(&self, other: &Self, factor: f32) -> Self {
+        Self {
+            r: self.r.add_scaled(&other.r, factor),
+            g: self.g.add_scaled(&other.g, factor),
+            b: self.b.add_scaled(&other.b, factor),
+            a: self.a.add_scaled(&other.a, factor),
+        }
+    }
+}
+
+metamatch::quote! {
+    [<for name in [Byte, Ushort, Ulong, Sbyte, Short, Int, Uint, Long]>]
+        impl ValueTrait for [<ident(str(name))>] {
+            fn scale(&mut self, factor: f32) -> bool {
+                *self = (*self as Double * factor as Double).round() as Self;
+                true
+            }
+
+            fn add_scaled(&self, other: &Self, factor: f32) -> Self {
+                (*self as Double + *other as Double * factor as Double).round() as Self
+            }
+
+            fn distance(&self, other: &Self) -> f32 {
+                (*self as Double - *other as Double).abs() as f32
+            }
+        }
+    [</for>]
+}
+
+impl ValueTrait for Float {
+    fn scale(&mut self, factor: f32) -> bool {
+        *self *= factor;
+        true
+    }
+
+    fn is_finite(&self) -> bool {
+        Float::is_finite(*self)
+    }
+
+    fn add_scaled(&self, other: &Self, factor: f32) -> Self {
+        self + other * factor
+    }
+
+    fn dot(&self, other: &Self) -> f32 {
+        self * other
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        (self - other).abs()
+    }
+}
+
+impl ValueTrait for Double {
+    fn scale(&mut self, factor: f32) -> bool {
+        *self *= factor as Double;
+        true
+    }
+
+    fn is_finite(&self) -> bool {
+        Double::is_finite(*self)
+    }
+
+    fn add_scaled(&self, other: &Self, factor: f32) -> Self {
+        self + other * factor as Double
+    }
+
+    fn dot(&self, other: &Self) -> f32 {
+        (self * other) as f32
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        (self - other).abs() as f32
+    }
+}
+
+metamatch::quote! {
+    [<for name in [Int, Uint, Long, Float, Double]>]
+        [<for range in 2..5>]
+            impl ValueTrait for [<ident(str(name) + str(range))>] {
+                fn scale(&mut self, factor: f32) -> bool {
+                    [<for field in 0..range>]
+                        [<let field_name = [x,y,z,w][field]>]
+                        self.[<ident(str(field_name))>].scale(factor);
+                    [</for>]
+                    true
+                }
+
+                fn is_finite(&self) -> bool {
+                    [
+                        [<for field in 0..range>]
+                            [<let field_name = [x,y,z,w][field]>]
+                            ValueTrait::is_finite(&self.[<ident(str(field_name))>]),
+                        [</for>]
+                    ].into_iter().all(|finite| finite)
+                }
+
+                fn add_scaled(&self, other: &Self, factor: f32) -> Self {
+                    Self {
+                        [<for field in 0..range>]
+                            [<let field_name = [x,y,z,w][field]>]
+                            [<ident(str(field_name))>]: self.[<ident(str(field_name))>].add_scaled(&other.[<ident(str(field_name))>], factor),
+                        [</for>]
+                    }
+                }
+
+                fn dot(&self, other: &Self) -> f32 {
+                    0.0
+                    [<for field in 0..range>]
+                        [<let field_name = [x,y,z,w][field]>]
+                        + self.[<ident(str(field_name))>].dot(&other.[<ident(str(field_name))>])
+                    [</for>]
+                }
+
+                fn distance(&self, other: &Self) -> f32 {
+                    (0.0
+                    [<for field in 0..range>]
+                        [<let field_name = [x,y,z,w][field]>]
+                        + self.[<ident(str(field_name))>].distance(&other.[<ident(str(field_name))>]).powi(2)
+                    [</for>]
+                    ).sqrt()
+                }
+            }
+        [</for>]
+    [</for>]
+}
+
+metamatch::quote! {
+    [<for type in [Float, Double]>]
+        [<for size in 2..5>]
+            impl ValueTrait for [<ident(str(type) + str(size) + "x" + str(size))>] {
+                fn scale(&mut self, factor: f32) -> bool {
+                    self.iter_mut().flatten().for_each(|v| { v.scale(factor); });
+                    true
+                }
+
+                fn is_finite(&self) -> bool {
+                    self.iter().flatten().all(|v| v.is_finite())
+                }
+
+                fn add_scaled(&self, other: &Self, factor: f32) -> Self {
+                    core::array::from_fn(|i| core::array::from_fn(|j| self[i][j].add_scaled(&other[i][j], factor)))
+                }
+
+                fn distance(&self, other: &Self) -> f32 {
+                    self.iter().flatten().zip(other.iter().flatten()).map(|(a, b)| a.distance(b).powi(2)).sum::<f32>().sqrt()
+                }
+            }
+        [</for>]
+    [</for>]
+}
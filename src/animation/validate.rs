@@ -0,0 +1,108 @@
+//! Checks for animations that would break Resonite, or the writer
+
+use alloc::vec::Vec;
+
+use super::Animation;
+
+impl Animation {
+    /// Checks every track for problems, returning all of them instead of stopping at the first one
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, ValidationError};
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [{"trackType": "Curve", "valueType": "float",
+    ///     "data": {"node": "n", "property": "p", "keyframes": [
+    ///         {"time": 0.0, "value": 0.0, "interpolation": "Linear"},
+    ///         {"time": 1.0, "value": 1.0, "interpolation": "Tangent", "leftTangent": [-1.0, 0.0]}
+    ///     ]}}]}"#).unwrap();
+    ///
+    /// assert_eq!(anim.validate(), Err(vec![ValidationError::MissingTangents { track: 0, keyframe: 1 }]));
+    /// ```
+    ///
+    /// Keyframes have to be sorted by time, [``TrackTrait::sort_keyframes``](super::types::TrackTrait::sort_keyframes) fixes tracks that aren't
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, ValidationError, types::TrackTrait};
+    ///
+    /// let track = |times: &str| format!(r#"{{"trackType": "Discrete", "valueType": "float",
+    ///     "data": {{"node": "n", "property": "p", "keyframes": [{}]}}}}"#,
+    ///     times.split(',').map(|time| format!(r#"{{"time": {time}, "value": 0.0}}"#)).collect::<Vec<_>>().join(","));
+    ///
+    /// let sorted: Animation = serde_json::from_str(&format!(r#"{{"tracks": [{}]}}"#, track("0,1,1,2"))).unwrap();
+    /// assert_eq!(sorted.validate(), Ok(()));
+    ///
+    /// let mut shuffled: Animation = serde_json::from_str(&format!(r#"{{"tracks": [{}, {}]}}"#, track("0,1"), track("0,2,1,3,0"))).unwrap();
+    /// assert_eq!(shuffled.validate(), Err(vec![
+    ///     ValidationError::UnsortedKeyframes { track: 1, keyframe: 2 },
+    ///     ValidationError::UnsortedKeyframes { track: 1, keyframe: 4 },
+    /// ]));
+    ///
+    /// shuffled.tracks[1].sort_keyframes();
+    /// assert_eq!(shuffled.validate(), Ok(()));
+    /// ```
+    ///
+    /// Bools and strings can't be interpolated, so curve tracks of them can only ``Hold`` and bezier tracks of them aren't allowed at all.
+    /// The AnimX reader refuses curves of them with tangents, and the writer drops their tangents.
+    ///
+    /// ```
+    /// use resonite_core::animation::{Animation, ValidationError};
+    ///
+    /// let anim: Animation = serde_json::from_str(r#"{"tracks": [
+    ///     {"trackType": "Curve", "valueType": "string", "data": {"node": "n", "property": "a", "keyframes": [
+    ///         {"time": 0.0, "value": "Hello", "interpolation": "Hold"},
+    ///         {"time": 1.0, "value": "World", "interpolation": "Linear"}
+    ///     ]}},
+    ///     {"trackType": "Bezier", "valueType": "bool", "data": {"node": "n", "property": "b", "keyframes": [
+    ///         {"time": 0.0, "value": true, "leftControl": true, "rightControl": false}
+    ///     ]}},
+    ///     {"trackType": "Curve", "valueType": "bool", "data": {"node": "n", "property": "c", "keyframes": [
+    ///         {"time": 0.0, "value": true, "interpolation": "Hold"}
+    ///     ]}}
+    /// ]}"#).unwrap();
+    ///
+    /// assert_eq!(anim.validate(), Err(vec![
+    ///     ValidationError::UninterpolatableCurve { track: 0, keyframe: 1 },
+    ///     ValidationError::UninterpolatableBezier { track: 1 },
+    /// ]));
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        for (index, track) in self.tracks.iter().enumerate() {
+            track.validate(index, &mut errors);
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// Checks that ``times`` never go backwards, sampling relies on that to find the keyframes around a time
+pub(super) fn validate_times(track: usize, times: impl Iterator<Item = f32>, errors: &mut Vec<ValidationError>) {
+    let mut previous = f32::NEG_INFINITY;
+    for (keyframe, time) in times.enumerate() {
+        if time.is_nan() {
+            errors.push(ValidationError::NanTime { track, keyframe });
+            continue;
+        }
+        if time < previous {
+            errors.push(ValidationError::UnsortedKeyframes { track, keyframe });
+        }
+        previous = time;
+    }
+}
+
+/// A problem found by [``Animation::validate``], ``track`` and ``keyframe`` are indices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// A ``Tangent`` or ``CubicBezier`` keyframe is missing its left or right tangent\
+    /// Sampling points a missing tangent straight at the keyframe next to it, but AnimX writes it flat as ``(0, zero)`` if any keyframe
+    /// of the track has a tangent, so the curve changes shape once it's read back
+    MissingTangents { track: usize, keyframe: usize },
+    /// The keyframe's time is earlier than the one before it
+    UnsortedKeyframes { track: usize, keyframe: usize },
+    /// The keyframe's time is NaN, which can't be sorted
+    NanTime { track: usize, keyframe: usize },
+    /// A keyframe in a curve track of bools or strings has tangents or an interpolation other than ``Hold``
+    UninterpolatableCurve { track: usize, keyframe: usize },
+    /// A bezier track of bools or strings, their control points can't mean anything
+    UninterpolatableBezier { track: usize },
+}
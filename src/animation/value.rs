@@ -0,0 +1,153 @@
+//! Type-erased keyframe values
+
+use core::any::Any;
+
+use super::types::*;
+
+metamatch::quote! {
+    /// A keyframe value of any [``ValueType``], for working with tracks without knowing their value type up front
+    ///
+    /// Arithmetic works on two values of the same type, component-wise for vectors, colors and matrices.
+    /// Bools and strings have no arithmetic, they come out unchanged (or step, for [``Value::lerp``])
+    ///
+    /// ```
+    /// use resonite_core::animation::{Value, TypeMismatch, types::{Float2, ValueType}};
+    ///
+    /// assert!(matches!(Value::Float(1.5).add(&Value::Float(2.0)), Ok(Value::Float(3.5))));
+    /// assert!(matches!(Value::Int(7).mul_scalar(0.5), Value::Int(4)));
+    ///
+    /// let sum = Value::Float2(Float2 { x: 1.0, y: 2.0 }).add(&Value::Float2(Float2 { x: 3.0, y: 4.0 })).unwrap();
+    /// assert!(matches!(sum, Value::Float2(Float2 { x: 4.0, y: 6.0 })));
+    ///
+    /// let mismatch = Value::Float(1.0).add(&Value::Double(1.0)).unwrap_err();
+    /// assert_eq!(mismatch, TypeMismatch { left: ValueType::Float, right: ValueType::Double });
+    /// ```
+    #[derive(Debug, Clone)]
+    pub enum Value {
+        [<for T in [
+            Byte, Ushort, Ulong, Sbyte, Short,
+            Bool, Bool2, Bool3, Bool4,
+            Int, Int2, Int3, Int4,
+            Uint, Uint2, Uint3, Uint4,
+            Long, Long2, Long3, Long4,
+            Float, Float2, Float3, Float4,
+            FloatQ, Float2x2, Float3x3, Float4x4,
+            Double, Double2, Double3, Double4,
+            DoubleQ, Double2x2, Double3x3, Double4x4,
+            Color, Color32, OptString,
+        ]>]
+            [<ident(str(T))>]([<ident(str(T))>]),
+        [</for>]
+    }
+}
+
+impl Value {
+    /// The value type of the value, quaternions report ``FloatQ``/``DoubleQ`` even though they're the same type as 4 component vectors
+    pub fn value_type(&self) -> ValueType {
+        metamatch::metamatch!(match self {
+            #[expand(for T in [
+                Byte, Ushort, Ulong, Sbyte, Short,
+                Bool, Bool2, Bool3, Bool4,
+                Int, Int2, Int3, Int4,
+                Uint, Uint2, Uint3, Uint4,
+                Long, Long2, Long3, Long4,
+                Float, Float2, Float3, Float4,
+                FloatQ, Float2x2, Float3x3, Float4x4,
+                Double, Double2, Double3, Double4,
+                DoubleQ, Double2x2, Double3x3, Double4x4,
+                Color, Color32, OptString,
+            ])]
+            Value::T(_) => ValueType::T,
+        })
+    }
+
+    /// ``self + other``, integers are rounded
+    pub fn add(&self, other: &Value) -> Result<Value, TypeMismatch> {
+        metamatch::metamatch!(match (self, other) {
+            #[expand(for T in [
+                Byte, Ushort, Ulong, Sbyte, Short,
+                Bool, Bool2, Bool3, Bool4,
+                Int, Int2, Int3, Int4,
+                Uint, Uint2, Uint3, Uint4,
+                Long, Long2, Long3, Long4,
+                Float, Float2, Float3, Float4,
+                FloatQ, Float2x2, Float3x3, Float4x4,
+                Double, Double2, Double3, Double4,
+                DoubleQ, Double2x2, Double3x3, Double4x4,
+                Color, Color32, OptString,
+            ])]
+            (Value::T(a), Value::T(b)) => Ok(Value::T(a.add_scaled(b, 1.0))),
+            _ => Err(self.mismatch(other)),
+        })
+    }
+
+    /// ``self * factor``, integers are rounded
+    pub fn mul_scalar(&self, factor: f32) -> Value {
+        metamatch::metamatch!(match self {
+            #[expand(for T in [
+                Byte, Ushort, Ulong, Sbyte, Short,
+                Bool, Bool2, Bool3, Bool4,
+                Int, Int2, Int3, Int4,
+                Uint, Uint2, Uint3, Uint4,
+                Long, Long2, Long3, Long4,
+                Float, Float2, Float3, Float4,
+                FloatQ, Float2x2, Float3x3, Float4x4,
+                Double, Double2, Double3, Double4,
+                DoubleQ, Double2x2, Double3x3, Double4x4,
+                Color, Color32, OptString,
+            ])]
+            // a + a * (factor - 1), since there's no zero value to scale from
+            Value::T(a) => Value::T(a.add_scaled(a, factor - 1.0)),
+        })
+    }
+
+    /// Linear interpolation from ``self`` to ``other``, see [``Lerp``]
+    pub fn lerp(&self, other: &Value, t: f32) -> Result<Value, TypeMismatch> {
+        metamatch::metamatch!(match (self, other) {
+            #[expand(for T in [
+                Byte, Ushort, Ulong, Sbyte, Short,
+                Bool, Bool2, Bool3, Bool4,
+                Int, Int2, Int3, Int4,
+                Uint, Uint2, Uint3, Uint4,
+                Long, Long2, Long3, Long4,
+                Float, Float2, Float3, Float4,
+                FloatQ, Float2x2, Float3x3, Float4x4,
+                Double, Double2, Double3, Double4,
+                DoubleQ, Double2x2, Double3x3, Double4x4,
+                Color, Color32, OptString,
+            ])]
+            (Value::T(a), Value::T(b)) => Ok(Value::T(a.lerp(b, t))),
+            _ => Err(self.mismatch(other)),
+        })
+    }
+
+    /// Wraps a value from a track of ``value_type``, ``None`` if ``value`` isn't of that type
+    pub(crate) fn from_any(value_type: ValueType, value: &dyn Any) -> Option<Value> {
+        metamatch::metamatch!(match value_type {
+            #[expand(for T in [
+                Byte, Ushort, Ulong, Sbyte, Short,
+                Bool, Bool2, Bool3, Bool4,
+                Int, Int2, Int3, Int4,
+                Uint, Uint2, Uint3, Uint4,
+                Long, Long2, Long3, Long4,
+                Float, Float2, Float3, Float4,
+                FloatQ, Float2x2, Float3x3, Float4x4,
+                Double, Double2, Double3, Double4,
+                DoubleQ, Double2x2, Double3x3, Double4x4,
+                Color, Color32, OptString,
+            ])]
+            ValueType::T => value.downcast_ref::<T>().cloned().map(Value::T),
+        })
+    }
+
+    fn mismatch(&self, other: &Value) -> TypeMismatch {
+        TypeMismatch { left: self.value_type(), right: other.value_type() }
+    }
+}
+
+/// Error for arithmetic on two [``Value``]s of different types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeMismatch {
+    pub left: ValueType,
+    pub right: ValueType,
+}
@@ -0,0 +1,30 @@
+//! Float functions that ``core`` doesn't have, backed by ``libm`` when ``std`` is off
+//!
+//! Only imported without ``std``, otherwise the inherent methods are used
+
+pub(crate) trait FloatExt: Sized {
+    fn floor(self) -> Self;
+    fn round(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn acos(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+}
+
+impl FloatExt for f32 {
+    fn floor(self) -> Self { libm::floorf(self) }
+    fn round(self) -> Self { libm::roundf(self) }
+    fn sqrt(self) -> Self { libm::sqrtf(self) }
+    fn sin(self) -> Self { libm::sinf(self) }
+    fn acos(self) -> Self { libm::acosf(self) }
+    fn powi(self, n: i32) -> Self { libm::powf(self, n as f32) }
+}
+
+impl FloatExt for f64 {
+    fn floor(self) -> Self { libm::floor(self) }
+    fn round(self) -> Self { libm::round(self) }
+    fn sqrt(self) -> Self { libm::sqrt(self) }
+    fn sin(self) -> Self { libm::sin(self) }
+    fn acos(self) -> Self { libm::acos(self) }
+    fn powi(self, n: i32) -> Self { libm::pow(self, n as f64) }
+}
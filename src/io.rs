@@ -0,0 +1,85 @@
+//! The bits of ``std::io`` the AnimX reader & writer use
+//!
+//! With the ``std`` feature these are just ``std::io``, without it they're a minimal stand-in
+//! that's only implemented for byte slices (reading) and ``Vec<u8>`` (writing)
+
+#[cfg(feature = "std")]
+pub(crate) use std::io::{ErrorKind, Read, Result, Write, sink};
+
+#[cfg(not(feature = "std"))]
+pub(crate) use no_std::*;
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use alloc::{boxed::Box, vec::Vec};
+
+    use crate::animation::AnimXError;
+
+    /// Slices and ``Vec``s can't fail in any other way than running out of data,
+    /// so that's reported as [``AnimXError::UnexpectedEof``] right away
+    pub(crate) type Error = AnimXError;
+
+    pub(crate) type Result<T> = core::result::Result<T, Error>;
+
+    pub(crate) trait Read {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+            let len = buf.len();
+            let mut rest = buf;
+            while !rest.is_empty() {
+                match self.read(rest)? {
+                    0 => return Err(AnimXError::UnexpectedEof(len)),
+                    read => rest = &mut rest[read..],
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let len = buf.len().min(self.len());
+            let (head, tail) = self.split_at(len);
+            buf[..len].copy_from_slice(head);
+            *self = tail;
+            Ok(len)
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for Box<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    pub(crate) trait Write {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+    }
+
+    impl Write for Vec<u8> {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            self.extend_from_slice(buf);
+            Ok(())
+        }
+    }
+
+    /// Throws away everything written to it, for counting bytes
+    pub(crate) struct Sink;
+
+    impl Write for Sink {
+        fn write_all(&mut self, _: &[u8]) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn sink() -> Sink {
+        Sink
+    }
+}
@@ -1,5 +1,16 @@
 #![doc(html_logo_url = "https://github.com/vlams1/resonite-core/raw/master/assets/logo.svg")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 //! Crate for resonite types (currently only animations)
+//!
+//! The ``std`` feature is on by default, without it the crate only needs ``alloc``.\
+//! AnimX is then read from slices and written to ``Vec``s, everything taking a ``Read`` or ``Write`` needs ``std``.
 
-pub mod animation;
\ No newline at end of file
+extern crate alloc;
+
+mod io;
+#[cfg(not(feature = "std"))]
+mod float;
+
+pub mod animation;
+pub mod prelude;
@@ -0,0 +1,36 @@
+//! The types & traits most code working with animations needs, all of them with a single ``use``
+//!
+//! ```
+//! use resonite_core::prelude::*;
+//!
+//! let anim = Animation::builder()
+//!     .name("Door")
+//!     .add_curve_track("Door", "Position", vec![
+//!         CurveKeyframe::linear(0.0, Float3::new(0.0, 0.0, 0.0)),
+//!         CurveKeyframe::linear(1.0, Float3::new(1.0, 0.0, 0.0)),
+//!     ])
+//!     .add_raw_track("Door", "Open", 0.5, vec![false, true, true])
+//!     .finish();
+//!
+//! let buf = anim.to_animx_bytes().unwrap();
+//! let read = Animation::from_animx_with_options(&buf[..], &ReadOptions::default()).unwrap();
+//!
+//! let position: &dyn TrackTrait = read.tracks[0].as_ref();
+//! assert_eq!((position.track_type(), position.value_type()), (TrackType::Curve, ValueType::Float3));
+//! let curve = position.downcast::<Track<CurveData<Float3>>>().unwrap();
+//! assert_eq!(curve.data.keyframes[0].interpolation, Interpolation::Linear);
+//! assert_eq!(curve.data.sample(0.5).unwrap().x, 0.5);
+//! assert!(read.tracks[1].downcast::<Track<RawData<bool>>>().is_some());
+//! ```
+
+pub use crate::animation::{
+    AnimJError, AnimXEncoding, AnimXError, Animation, AnimationBuilder, BezierData, BezierKeyframe, CurveData, CurveKeyframe,
+    DiscreteData, DiscreteKeyframe, Interpolation, RawData, ReadOptions, Track, Value, WriteOptions,
+    types::{
+        Bool2, Bool3, Bool4, Color, Color32,
+        Double2, Double2x2, Double3, Double3x3, Double4, Double4x4, DoubleQ,
+        Float2, Float2x2, Float3, Float3x3, Float4, Float4x4, FloatQ,
+        Int2, Int3, Int4, Lerp, Long2, Long3, Long4, OptString,
+        TrackTrait, TrackType, Uint2, Uint3, Uint4, ValueType,
+    },
+};
@@ -0,0 +1,57 @@
+//! Floats have to survive AnimX -> AnimJ -> AnimX with the exact same bits, however they're written
+
+use resonite_core::animation::{AnimJFloats, AnimJOptions, Animation, CurveKeyframe, Interpolation, types::Double3};
+
+/// Every finite ``f32`` from a fixed xorshift, so the same values are checked every run
+fn floats(count: usize) -> Vec<f32> {
+    let mut state = 0x2545f491u32;
+    std::iter::repeat_with(|| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        f32::from_bits(state)
+    }).filter(|f| f.is_finite()).take(count).collect()
+}
+
+fn round_trip(anim: &Animation) {
+    let animx = anim.to_animx_bytes().unwrap();
+    for floats in [AnimJFloats::Wide, AnimJFloats::Shortest] {
+        let json = Animation::from_animx_slice(&animx).unwrap().to_animj_string_with_options(&AnimJOptions { floats }).unwrap();
+        let back = Animation::from_animj_str(&json).unwrap().to_animx_bytes().unwrap();
+        assert!(back == animx, "{floats:?} changed the bits");
+    }
+}
+
+#[test]
+fn tenth_is_bit_identical() {
+    let anim = Animation::builder().global_duration(0.1).add_raw_track("n", "p", 0.1, vec![0.1f32]).finish();
+    round_trip(&anim);
+
+    let shortest = anim.to_animj_string_with_options(&AnimJOptions { floats: AnimJFloats::Shortest }).unwrap();
+    assert!(!shortest.contains("0.10000000149011612"), "{shortest}");
+}
+
+#[test]
+fn every_float_is_bit_identical() {
+    let values = floats(100_000);
+    let times = floats(1000);
+    let anim = Animation::builder()
+        .add_raw_track("n", "raw", 1.0 / 3.0, values.clone())
+        .add_curve_track("n", "curve", times.iter().zip(&values).map(|(&time, &value)| CurveKeyframe {
+            time,
+            value,
+            interpolation: Interpolation::Tangent,
+            left_tangent: Some((-time, value)),
+            right_tangent: Some((time, -value)),
+        }).collect())
+        // Doubles only get their times shortened, the values have to stay wide
+        .add_curve_track("n", "double", times.iter().map(|&time| CurveKeyframe {
+            time,
+            value: Double3 { x: 0.1, y: time as f64 / 3.0, z: 1e300 },
+            interpolation: Interpolation::Tangent,
+            left_tangent: Some((time, Double3 { x: 0.1, y: 0.2, z: 0.3 })),
+            right_tangent: Some((-time, Double3 { x: 1.0 / 3.0, y: 0.0, z: -1.0 })),
+        }).collect())
+        .finish();
+    round_trip(&anim);
+}
@@ -0,0 +1,59 @@
+//! [``Animation::from_animx_async``] has to read every fixture the same as the blocking reader, however the bytes trickle in
+
+#![cfg(feature = "tokio")]
+
+use std::{fs, path::Path};
+
+use resonite_core::animation::{AnimXError, Animation, ReadOptions, types::OptString};
+use tokio::io::AsyncWriteExt;
+
+/// Reads ``animx`` through a pipe that only holds ``capacity`` bytes at a time
+async fn read_piped(animx: &[u8], capacity: usize, options: &ReadOptions) -> Result<Animation, AnimXError> {
+    let (mut writer, reader) = tokio::io::duplex(capacity);
+    let write = async {
+        writer.write_all(animx).await.unwrap();
+        drop(writer);
+    };
+    tokio::join!(write, Animation::from_animx_async_with_options(reader, options)).1
+}
+
+#[tokio::test]
+async fn async_matches_blocking() {
+    let mut dirs = vec!["tests/corpus"];
+    if cfg!(feature = "compression") {
+        dirs.push("tests/corpus/compressed");
+    }
+
+    for entry in dirs.into_iter().flat_map(|dir| fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(dir)).unwrap()) {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|e| e != "animx") {
+            continue;
+        }
+
+        let animx = fs::read(&path).unwrap();
+        for capacity in [1, 7, 4096] {
+            let read = read_piped(&animx, capacity, &ReadOptions::default()).await.unwrap_or_else(|e| panic!("couldn't read {}: {e:?}", path.display()));
+            assert!(read.bit_identical(&Animation::from_animx_slice(&animx).unwrap()), "{} reads differently async", path.display());
+        }
+    }
+}
+
+#[tokio::test]
+async fn strings_and_errors() {
+    let anim = Animation::builder()
+        .add_raw_track("n", "text", 0.5, (0..100).map(|i| OptString(format!("frame {i}"))).collect())
+        .add_raw_track("n", "p", 0.5, vec![1.0f32; 100])
+        .finish();
+    let mut animx = Vec::new();
+    anim.write_animx_async(&mut animx).await.unwrap();
+    assert_eq!(animx, anim.to_animx_bytes().unwrap());
+
+    assert!(read_piped(&animx, 3, &ReadOptions::default()).await.unwrap().bit_identical(&anim));
+    let truncated = read_piped(&animx[..animx.len() - 1], 3, &ReadOptions::default()).await;
+    assert!(matches!(truncated, Err(AnimXError::UnexpectedEof(_))));
+
+    animx.push(0);
+    let strict = ReadOptions { reject_trailing_data: true, ..Default::default() };
+    assert!(read_piped(&animx, 3, &ReadOptions::default()).await.is_ok());
+    assert!(matches!(read_piped(&animx, 3, &strict).await, Err(AnimXError::TrailingData)));
+}
@@ -0,0 +1,53 @@
+//! Scalar tracks have to come out of ``combine_scalar_tracks`` and ``split_vector_track`` with the same values they went in with
+
+use resonite_core::animation::{Animation, CurveData, CurveKeyframe, DiscreteData, DiscreteKeyframe, Track, types::{Float, Float3, TrackType, ValueType}};
+
+fn sine(phase: f32) -> Vec<DiscreteKeyframe<Float>> {
+    (0..=20).map(|i| i as f32 * 0.1).map(|time| DiscreteKeyframe { time, value: (time + phase).sin() }).collect()
+}
+
+#[test]
+fn combine_sines() {
+    let mut anim = Animation::builder()
+        .add_discrete_track("n", "other", vec![DiscreteKeyframe { time: 0.0, value: 1.0f32 }])
+        .add_discrete_track("n", "p.x", sine(0.0))
+        .add_discrete_track("n", "p.y", sine(1.0))
+        .add_discrete_track("n", "p.z", sine(2.0))
+        .finish();
+
+    anim.combine_scalar_tracks(Some("n"), "p", ValueType::Float3).unwrap();
+    assert_eq!(anim.tracks.len(), 2);
+    assert_eq!(anim.tracks[1].property(), Some("p"));
+    assert_eq!(anim.tracks[1].track_type(), TrackType::Discrete);
+
+    let track = anim.tracks[1].downcast::<Track<DiscreteData<Float3>>>().unwrap();
+    assert_eq!(track.data.keyframes.len(), 21);
+    for k in &track.data.keyframes {
+        assert_eq!(k.value, Float3::new(k.time.sin(), (k.time + 1.0).sin(), (k.time + 2.0).sin()), "at {}", k.time);
+    }
+}
+
+#[test]
+fn split_curve() {
+    let keyframes = vec![CurveKeyframe::linear(0.0, Float3::new(1.0, 2.0, 3.0)), CurveKeyframe::linear(1.0, Float3::new(-1.0, 0.0, 5.0))];
+    let mut anim = Animation::builder().add_curve_track("n", "p", keyframes).finish();
+
+    anim.split_vector_track(Some("n"), "p").unwrap();
+    let properties: Vec<_> = anim.tracks.iter().map(|t| t.property()).collect();
+    assert_eq!(properties, [Some("p.x"), Some("p.y"), Some("p.z")]);
+
+    let values = |index: usize| {
+        let track = anim.tracks[index].downcast::<Track<CurveData<Float>>>().unwrap();
+        track.data.keyframes.iter().map(|k| (k.time, k.value)).collect::<Vec<_>>()
+    };
+    assert_eq!(values(0), [(0.0, 1.0), (1.0, -1.0)]);
+    assert_eq!(values(1), [(0.0, 2.0), (1.0, 0.0)]);
+    assert_eq!(values(2), [(0.0, 3.0), (1.0, 5.0)]);
+
+    // Putting them back together gives the same curve
+    anim.combine_scalar_tracks(Some("n"), "p", ValueType::Float3).unwrap();
+    let track = anim.tracks[0].downcast::<Track<CurveData<Float3>>>().unwrap();
+    assert_eq!(track.data.keyframes[1].value, Float3::new(-1.0, 0.0, 5.0));
+
+    assert!(anim.split_vector_track(Some("n"), "q").is_err());
+}
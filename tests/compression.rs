@@ -0,0 +1,58 @@
+//! Compressed fixtures in ``tests/corpus/compressed`` have to read the same as their uncompressed counterpart in ``tests/corpus``
+
+#![cfg(feature = "compression")]
+
+use std::{fs, path::Path};
+
+use resonite_core::animation::{AnimXEncoding, AnimXError, Animation, ReadOptions, WriteOptions};
+
+#[test]
+fn compressed_matches_plain() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+
+    for (file, encoding) in [("raw_deflate", AnimXEncoding::Deflate), ("raw_lzma", AnimXEncoding::Lzma)] {
+        let compressed = fs::read(corpus.join("compressed").join(file).with_extension("animx")).unwrap();
+        assert_eq!(Animation::read_header(&compressed[..]).unwrap().encoding, encoding);
+
+        let anim = Animation::from_animx(&compressed[..]).unwrap_or_else(|e| panic!("couldn't read {file}: {e:?}"));
+        let plain = fs::read(corpus.join("raw.animx")).unwrap();
+        assert_eq!(anim.to_animx_bytes().unwrap(), plain, "{file} doesn't match raw.animx");
+    }
+}
+
+#[test]
+fn compressed_round_trip() {
+    let plain = fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/curve.animx")).unwrap();
+    let anim = Animation::from_animx(&plain[..]).unwrap();
+
+    for encoding in [AnimXEncoding::Deflate, AnimXEncoding::Lzma] {
+        let mut compressed = Vec::new();
+        let written = anim.write_animx_with_options(&mut compressed, &WriteOptions { encoding, ..Default::default() }).unwrap();
+        assert_eq!(written as usize, compressed.len());
+
+        let header = Animation::read_header(&compressed[..]).unwrap();
+        assert_eq!((header.name.as_deref(), header.track_count, header.encoding), (Some("Curve"), 2, encoding));
+
+        let read = Animation::from_animx(&compressed[..]).unwrap();
+        assert_eq!(read.to_animx_bytes().unwrap(), plain, "{encoding:?} didn't round trip");
+    }
+}
+
+#[test]
+fn decompressed_len_is_limited() {
+    let plain = fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/curve.animx")).unwrap();
+    let anim = Animation::from_animx(&plain[..]).unwrap();
+
+    for encoding in [AnimXEncoding::Deflate, AnimXEncoding::Lzma] {
+        let mut compressed = Vec::new();
+        anim.write_animx_with_options(&mut compressed, &WriteOptions { encoding, ..Default::default() }).unwrap();
+
+        // The tracks are a bit shorter than the whole plain file, which has the header in front
+        let fits = ReadOptions { max_decompressed_len: plain.len(), ..Default::default() };
+        assert!(Animation::from_animx_with_options(&compressed[..], &fits).is_ok(), "{encoding:?} should fit");
+
+        let tight = ReadOptions { max_decompressed_len: 16, ..Default::default() };
+        let result = Animation::from_animx_with_options(&compressed[..], &tight);
+        assert!(matches!(result, Err(AnimXError::IoError(e)) if e.kind() == std::io::ErrorKind::InvalidData), "{encoding:?} wasn't limited");
+    }
+}
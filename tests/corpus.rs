@@ -0,0 +1,90 @@
+//! Round trips every fixture in ``tests/corpus`` to catch changes to the AnimX layout
+//!
+//! Every ``.animx`` file has to come out of ``from_animx`` -> ``write_animx`` byte for byte the same,
+//! and if there's an ``.animj`` file with the same name it has to write those same bytes too.\
+//! Going through ``serde_json::to_string`` -> ``from_str`` has to give back the same animation as well.\
+//! The fixtures at the top were written by this crate, files exported from Resonite go in ``tests/corpus/resonite`` as they are.\
+//! Fixtures are never regenerated, a fixture that stops round tripping means the reader or writer changed the layout.
+
+use std::{fs, path::Path};
+
+use resonite_core::animation::{Animation, CurveData, Track, types::{Float, Float2}};
+
+#[test]
+fn corpus_round_trips() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    assert!(round_trip_all(&corpus) > 0, "no fixtures in {}", corpus.display());
+
+    // Exports from Resonite are the ones that matter, but there's nothing to check until one is added
+    round_trip_all(&corpus.join("resonite"));
+}
+
+/// Round trips every ``.animx`` file in ``dir``, returns how many there were
+fn round_trip_all(dir: &Path) -> usize {
+    let mut checked = 0;
+
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|e| e != "animx") {
+            continue;
+        }
+
+        let animx = fs::read(&path).unwrap();
+        let anim = Animation::from_animx(&animx[..]).unwrap_or_else(|e| panic!("couldn't read {}: {e:?}", path.display()));
+        let mut written = Vec::new();
+        anim.write_animx(&mut written).unwrap();
+        assert_eq!(written, animx, "{} changed after a round trip", path.display());
+
+        // Writing it back out as AnimJ can't lose anything either
+        let json = serde_json::to_string(&anim).unwrap();
+        let reparsed: Animation = serde_json::from_str(&json).unwrap_or_else(|e| panic!("couldn't parse the AnimJ written for {}: {e}", path.display()));
+        assert!(reparsed.bit_identical(&anim), "{} changed after an AnimJ round trip", path.display());
+
+        if let Ok(animj) = fs::read_to_string(path.with_extension("animj")) {
+            let anim: Animation = serde_json::from_str(&animj).unwrap_or_else(|e| panic!("couldn't parse the AnimJ of {}: {e}", path.display()));
+            let mut written = Vec::new();
+            anim.write_animx(&mut written).unwrap();
+            assert_eq!(written, animx, "the AnimJ of {} doesn't match it", path.display());
+        }
+
+        checked += 1;
+    }
+
+    checked
+}
+
+#[test]
+fn tangent_times_survive() {
+    let animx = fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/tangent.animx")).unwrap();
+    let anim = Animation::from_animx(&animx[..]).unwrap();
+
+    let angle = &anim.tracks[0].as_any().downcast_ref::<Track<CurveData<Float>>>().unwrap().data;
+    let times: Vec<_> = angle.keyframes.iter().map(|k| (k.left_tangent.unwrap().0, k.right_tangent.unwrap().0)).collect();
+    assert_eq!(times, [(0.0, 0.25), (-0.5, 0.125), (0.0, 0.0)]);
+    assert_eq!(angle.keyframes[1].left_tangent, Some((-0.5, -15.0)));
+
+    let offset = &anim.tracks[1].as_any().downcast_ref::<Track<CurveData<Float2>>>().unwrap().data;
+    let times: Vec<_> = offset.keyframes.iter().map(|k| (k.left_tangent.as_ref().unwrap().0, k.right_tangent.as_ref().unwrap().0)).collect();
+    assert_eq!(times, [(-0.75, 0.375), (-0.5, 0.5)]);
+}
+
+#[test]
+fn trim_keeps_curve_shape() {
+    let animx = fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/tangent.animx")).unwrap();
+    let anim = Animation::from_animx(&animx[..]).unwrap();
+
+    // Both ends cut through a tangent segment, and the end is past the last keyframe of the second track
+    let clip = anim.trim(0.4, 1.8);
+    assert_eq!(clip.tracks[0].keyframe_count(), 3);
+    assert_eq!(clip.tracks[1].keyframe_count(), 3);
+
+    for i in 0..=14 {
+        let time = i as f32 * 0.1;
+        let angle = |anim: &Animation, time| *anim.tracks[0].sample_any(time).unwrap().downcast::<Float>().unwrap();
+        assert!((angle(&anim, time + 0.4) - angle(&clip, time)).abs() < 1e-3, "angle differs at {time}");
+
+        let offset = |anim: &Animation, time| *anim.tracks[1].sample_any(time).unwrap().downcast::<Float2>().unwrap();
+        let (a, b) = (offset(&anim, time + 0.4), offset(&clip, time));
+        assert!((a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4, "offset differs at {time}");
+    }
+}
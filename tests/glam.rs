@@ -0,0 +1,29 @@
+//! Values have to come back from glam unchanged, and matrices have to keep their meaning on the way there
+
+#![cfg(feature = "glam")]
+
+use glam::{Mat4, Quat, Vec3};
+use resonite_core::animation::{glam::{from_mat4, to_mat4}, types::{Float3, Float4x4, FloatQ}};
+
+#[test]
+fn matrix_round_trip() {
+    let m: Float4x4 = std::array::from_fn(|row| std::array::from_fn(|column| (row * 4 + column) as f32));
+    let mat = to_mat4(&m);
+    assert_eq!(from_mat4(mat), m);
+
+    // Rows stay rows
+    assert_eq!(mat.row(1).to_array(), m[1]);
+    assert_eq!(mat.col(3).to_array(), [3.0, 7.0, 11.0, 15.0]);
+}
+
+#[test]
+fn transform_round_trip() {
+    let rotation = Quat::from_rotation_z(30f32.to_radians());
+    let mat = Mat4::from_rotation_translation(rotation, Vec3::new(1.0, 2.0, 3.0));
+    assert_eq!(to_mat4(&from_mat4(mat)), mat);
+
+    let q = FloatQ::from(rotation);
+    assert_eq!(Quat::from(q), rotation);
+    let point: Float3 = mat.transform_point3(Vec3::X).into();
+    assert!((point.x - (1.0 + 30f32.to_radians().cos())).abs() < 1e-6);
+}
@@ -0,0 +1,68 @@
+//! [``Animation::from_animx_parallel``] has to read every fixture in ``tests/corpus`` the same as the serial reader
+
+#![cfg(feature = "rayon")]
+
+use std::{fs, path::Path};
+
+use resonite_core::animation::{AnimXError, Animation, ReadOptions};
+
+#[test]
+fn parallel_matches_serial() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+
+    for entry in fs::read_dir(&corpus).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|e| e != "animx") {
+            continue;
+        }
+
+        let animx = fs::read(&path).unwrap();
+        let parallel = Animation::from_animx_parallel(&animx, &ReadOptions::default()).unwrap_or_else(|e| panic!("couldn't read {}: {e:?}", path.display()));
+        assert!(parallel.bit_identical(&Animation::from_animx_slice(&animx).unwrap()), "{} reads differently in parallel", path.display());
+    }
+}
+
+#[test]
+fn truncated_track_is_an_error() {
+    let animx = fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/curve.animx")).unwrap();
+    assert!(matches!(Animation::from_animx_parallel(&animx[..animx.len() - 1], &ReadOptions::default()), Err(AnimXError::UnexpectedEof(_))));
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn parallel_matches_serial_for_every_type() {
+    use rand::{SeedableRng, rngs::StdRng};
+    use resonite_core::animation::random::RandomSpec;
+
+    let spec = RandomSpec { tracks: 200, ..Default::default() };
+    let animx = Animation::random(&mut StdRng::seed_from_u64(283), &spec).to_animx_bytes().unwrap();
+    let parallel = Animation::from_animx_parallel(&animx, &ReadOptions::default()).unwrap();
+    assert!(parallel.bit_identical(&Animation::from_animx_slice(&animx).unwrap()));
+}
+
+#[test]
+fn trailing_data_is_rejected() {
+    let mut animx = fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/curve.animx")).unwrap();
+    animx.push(0);
+    let strict = ReadOptions { reject_trailing_data: true, ..Default::default() };
+    assert!(Animation::from_animx_parallel(&animx, &ReadOptions::default()).is_ok());
+    assert!(matches!(Animation::from_animx_parallel(&animx, &strict), Err(AnimXError::TrailingData)));
+}
+
+#[test]
+fn errors_have_the_same_offset() {
+    let mut animx = Animation::builder()
+        .add_raw_track("n", "a", 0.1, vec![1.0f32; 10])
+        .add_raw_track("n", "b", 0.1, vec![1.0f32; 10])
+        .finish()
+        .to_animx_bytes()
+        .unwrap();
+    // The track type of the second track, each track is 2 type bytes, 2 names of 2 bytes, a count, an interval and the frames
+    let offset = Animation::builder().finish().to_animx_bytes().unwrap().len() + 2 + 4 + 1 + 4 + 40;
+    animx[offset] = 0xee;
+
+    let serial = Animation::from_animx_slice(&animx).unwrap_err();
+    let parallel = Animation::from_animx_parallel(&animx, &ReadOptions::default()).unwrap_err();
+    assert!(matches!(serial, AnimXError::IncorrectTrackType { offset: o, byte: 0xee } if o == offset as u64));
+    assert_eq!(parallel.to_string(), serial.to_string());
+}
@@ -0,0 +1,23 @@
+//! Animations from [``Animation::random``] have to be valid and survive an AnimX round trip unchanged
+
+#![cfg(feature = "testing")]
+
+use rand::{SeedableRng, rngs::StdRng};
+use resonite_core::animation::{Animation, random::RandomSpec};
+
+#[test]
+fn random_is_valid() {
+    for seed in 0..32 {
+        let anim = Animation::random(&mut StdRng::seed_from_u64(seed), &RandomSpec::default());
+        assert_eq!(anim.validate(), Ok(()), "seed {seed}");
+    }
+}
+
+#[cfg(feature = "proptest")]
+proptest::proptest! {
+    #[test]
+    fn animx_round_trip(anim in resonite_core::animation::random::animation_strategy(RandomSpec::default())) {
+        let read = Animation::from_animx(&anim.to_animx_bytes().unwrap()[..]).unwrap();
+        proptest::prop_assert!(read.bit_identical(&anim));
+    }
+}